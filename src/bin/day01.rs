@@ -3,7 +3,6 @@ use advent_of_code_2022::lines;
 fn main() {
     let mut calories = lines()
         .split(|line| line.is_empty())
-        .into_iter()
         .map(|seq| {
             seq.iter()
                 .map(|num| num.parse::<i64>().unwrap())
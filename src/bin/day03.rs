@@ -45,10 +45,10 @@ fn and3(a: &str, b: &str, c: &str) -> Vec<char> {
 
 fn priority(chr: char) -> usize {
     let c = chr as u8;
-    if (b'a'..=b'z').contains(&c) {
+    if c.is_ascii_lowercase() {
         return (c - b'a' + 1) as usize;
     }
-    if (b'A'..=b'Z').contains(&c) {
+    if c.is_ascii_uppercase() {
         return (c - b'A' + 1 + 26) as usize;
     }
     panic!("Unexpected char: {}", chr)
@@ -113,7 +113,6 @@ fn parse_state(lines: &[String]) -> State {
 fn parse_move(line: &str) -> Move {
     let parsed = line
         .split_ascii_whitespace()
-        .into_iter()
         .filter_map(|item| item.parse::<usize>().ok())
         .collect::<Vec<_>>();
 
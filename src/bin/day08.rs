@@ -33,7 +33,6 @@ impl Grid {
             .into_iter()
             .map(|line| {
                 line.chars()
-                    .into_iter()
                     .map(|n| n.to_digit(10).unwrap() as usize)
                     .collect()
             })
@@ -52,7 +51,6 @@ impl Grid {
 
     fn row(&self, row: usize) -> Vec<(Cell, usize)> {
         (0..self.cols)
-            .into_iter()
             .map(|col| {
                 let cell = Cell::of(row, col);
                 let val = self.at(&cell);
@@ -63,7 +61,6 @@ impl Grid {
 
     fn col(&self, col: usize) -> Vec<(Cell, usize)> {
         (0..self.rows)
-            .into_iter()
             .map(|row| {
                 let cell = Cell::of(row, col);
                 let val = self.at(&cell);
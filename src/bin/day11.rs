@@ -88,7 +88,6 @@ fn parse(lines: &[String]) -> Monkey {
         .strip_prefix("  Starting items: ")
         .unwrap_or_default()
         .split(", ")
-        .into_iter()
         .map(|id| id.parse::<isize>().unwrap())
         .collect::<Vec<_>>();
 
@@ -1,4 +1,7 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::{
+    collections::{HashMap, VecDeque},
+    iter::once,
+};
 
 use advent_of_code_2022::lines;
 
@@ -7,51 +10,39 @@ fn main() {
 
     let start = grid.find('S')[0];
     let end = grid.find('E')[0];
-    println!("{}", bfs(&grid, &start)[&end]);
+    let steps = towards(&grid, &end);
+    println!("{}", steps[&start]);
 
     let min = grid
         .find('a')
         .into_iter()
-        .filter_map(|cell| {
-            let step = bfs(&grid, &cell);
-            step.get(&end).cloned()
-        })
+        .chain(once(start))
+        .filter_map(|cell| steps.get(&cell).cloned())
         .min()
         .unwrap_or_default();
     println!("{}", min);
 }
 
-fn bfs(grid: &Grid, cell: &Cell) -> HashMap<Cell, usize> {
-    let mut seen: HashSet<Cell> = HashSet::new();
+/// Fewest steps from every cell that can reach `end`, found in one BFS from
+/// `end` walking the climbing rule backwards: `prev -> cell` is a valid step
+/// when `cell` is at most one higher than `prev`.
+fn towards(grid: &Grid, end: &Cell) -> HashMap<Cell, usize> {
     let mut step: HashMap<Cell, usize> = HashMap::new();
-
-    step.insert(*cell, 0);
+    step.insert(*end, 0);
 
     let mut queue = VecDeque::new();
-    queue.push_back(*cell);
-
-    while !queue.is_empty() {
-        let cell = queue.pop_front().unwrap();
-        let a = grid.cell(&cell).unwrap();
+    queue.push_back(*end);
 
-        for next in grid.adj(&cell) {
-            if seen.contains(&next) {
+    while let Some(cell) = queue.pop_front() {
+        let b = grid.cell(&cell).unwrap();
+        for prev in grid.adj(&cell) {
+            if step.contains_key(&prev) {
                 continue;
             }
-            if let Some(b) = grid.cell(&next) {
-                let d = dist(a, b);
-                if d <= 1 {
-                    if step.get(&next).cloned().unwrap_or(usize::MAX) > step[&cell] + 1 {
-                        step.insert(next, step[&cell] + 1);
-                    }
-
-                    if b == 'E' {
-                        return step;
-                    }
-
-                    seen.insert(next);
-                    queue.push_back(next);
-                }
+            let a = grid.cell(&prev).unwrap();
+            if dist(a, b) <= 1 {
+                step.insert(prev, step[&cell] + 1);
+                queue.push_back(prev);
             }
         }
     }
@@ -147,3 +138,32 @@ fn dist(a: char, b: char) -> isize {
     let b = b as isize;
     b - a
 }
+
+#[cfg(test)]
+mod day12 {
+    use super::*;
+
+    const EXAMPLE: &str = "Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi";
+
+    #[test]
+    fn test_example() {
+        let grid = Grid::from_lines(EXAMPLE.lines().map(String::from).collect());
+        let end = grid.find('E')[0];
+        let steps = towards(&grid, &end);
+        assert_eq!(steps[&grid.find('S')[0]], 31);
+        assert_eq!(
+            grid.find('a')
+                .iter()
+                .filter_map(|cell| steps.get(cell))
+                .min(),
+            Some(&29)
+        );
+        assert_eq!(steps[&end], 0);
+        assert_eq!(steps[&Cell { row: 2, col: 4 }], 1);
+        assert_eq!(steps[&Cell { row: 4, col: 0 }], 29);
+    }
+}
@@ -7,7 +7,6 @@ use advent_of_code_2022::lines;
 fn main() {
     let pairs: Vec<Vec<Node>> = lines()
         .split(|line| line.is_empty())
-        .into_iter()
         .map(|pair| pair.iter().map(|line| Node::from_str(line)).collect())
         .collect();
 
@@ -66,17 +65,17 @@ impl Node {
 
 impl PartialOrd for Node {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match (self, other) {
-            (Node::Val(x), Node::Val(y)) => x.partial_cmp(y),
-            (Node::Vec(x), Node::Vec(y)) => x.partial_cmp(y),
-            (Node::Val(_), Node::Vec(_)) => Node::Vec(vec![self.clone()]).partial_cmp(other),
-            (Node::Vec(_), Node::Val(_)) => self.partial_cmp(&Node::Vec(vec![other.clone()])),
-        }
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Node {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        match (self, other) {
+            (Node::Val(x), Node::Val(y)) => x.cmp(y),
+            (Node::Vec(x), Node::Vec(y)) => x.cmp(y),
+            (Node::Val(_), Node::Vec(_)) => Node::Vec(vec![self.clone()]).cmp(other),
+            (Node::Vec(_), Node::Val(_)) => self.cmp(&Node::Vec(vec![other.clone()])),
+        }
     }
 }
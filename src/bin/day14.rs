@@ -1,48 +1,293 @@
-use std::{collections::HashSet, env::args, iter::once};
+use std::{collections::HashSet, env::args};
 
 use advent_of_code_2022::lines;
 
 fn main() {
-    let verbose = args().nth(1).map(|arg| arg == "--dump").unwrap_or_default();
+    let opts = args().skip(1).collect::<Vec<_>>();
+    let opt = |name: &str| {
+        opts.iter()
+            .position(|opt| opt == name)
+            .and_then(|idx| opts.get(idx + 1))
+    };
+    let verbose = opts.iter().any(|opt| opt == "--dump");
     let mut grid = Grid::from_lines(lines());
 
     let src = Cell::at(0, 500);
 
-    let (min, max) = grid.bounds(&src);
-    while let Some(cell) = drop(&grid, src, |next| next.fits(&min, &max)) {
-        grid.sand.insert(cell);
+    let custom = ["--source", "--floor", "--stop"]
+        .iter()
+        .any(|name| opt(name).is_some());
+    let setups = if custom {
+        let sources = opts
+            .iter()
+            .zip(opts.iter().skip(1))
+            .filter(|(opt, _)| *opt == "--source")
+            .map(|(_, arg)| parse(arg)[0])
+            .collect::<Vec<_>>();
+        vec![Setup {
+            sources: if sources.is_empty() {
+                vec![src]
+            } else {
+                sources
+            },
+            floor: opt("--floor").and_then(|arg| Setup::floor(arg)),
+            stop: opt("--stop")
+                .map(|arg| Stop::parse(arg))
+                .unwrap_or(Stop::Blocked),
+        }]
+    } else {
+        vec![Setup::part1(src), Setup::part2(src)]
+    };
+
+    let frames = opt("--frames").map(|arg| match arg.parse::<usize>() {
+        Ok(n) if n > 0 => n,
+        _ => panic!("--frames expects a positive number, got '{}'", arg),
+    });
+    let window = opt("--crop").map(|arg| {
+        let (a, b) = arg.split_once(':').expect("--crop <col>,<row>:<col>,<row>");
+        (parse(a)[0], parse(b)[0])
+    });
+
+    for setup in setups {
+        let mut view = View {
+            sources: setup.sources.clone(),
+            falling: vec![],
+            window,
+        };
+        let mut cave = Cave::new(&grid, &setup);
+        cave.run(setup.stop, |cave, dropped| {
+            if frames.map(|n| dropped % n == 0).unwrap_or_default() {
+                grid.sand = cave.sand_cells().collect();
+                grid.floor = setup.floor.map(|_| cave.floor);
+                view.falling = cave.falling();
+                println!("{} grains:\n{}\n", dropped, grid.dump(&view));
+            }
+        });
+        println!("{}", cave.sand);
+
+        if verbose {
+            grid.sand = cave.sand_cells().collect();
+            grid.floor = setup.floor.map(|_| cave.floor);
+            view.falling = vec![];
+            println!("{}", grid.dump(&view));
+        }
     }
-    println!("{}", grid.sand.len());
+}
+
+/// When a run of the simulation ends.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Stop {
+    /// The first grain falls past the lowest rock.
+    Abyss,
+    /// Every source is blocked by sand or only leaks into the abyss.
+    Blocked,
+    /// After this many grains were dropped, counting the lost ones.
+    Grains(usize),
+}
 
-    if verbose {
-        println!("{}", grid.dump());
+impl Stop {
+    fn parse(arg: &str) -> Self {
+        match arg {
+            "abyss" => Stop::Abyss,
+            "blocked" => Stop::Blocked,
+            n => Stop::Grains(
+                n.parse()
+                    .unwrap_or_else(|_| panic!("Expected abyss, blocked or a number: '{}'", n)),
+            ),
+        }
     }
+}
 
-    let floor = max.row + 2;
-    grid.floor = Some(floor);
+/// Sources take turns dropping a grain, in order. `floor` is the depth of an
+/// endless floor below the lowest rock; without one sand falls forever.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Setup {
+    sources: Vec<Cell>,
+    floor: Option<isize>,
+    stop: Stop,
+}
 
-    while let Some(cell) = drop(&grid, src, |_| true) {
-        grid.sand.insert(cell);
-        if cell == src {
-            break;
+impl Setup {
+    fn part1(src: Cell) -> Self {
+        Self {
+            sources: vec![src],
+            floor: None,
+            stop: Stop::Abyss,
         }
     }
-    println!("{}", grid.sand.len());
 
-    if verbose {
-        println!("{}", grid.dump());
+    fn part2(src: Cell) -> Self {
+        Self {
+            sources: vec![src],
+            floor: Some(2),
+            stop: Stop::Blocked,
+        }
     }
+
+    fn floor(arg: &str) -> Option<isize> {
+        (arg != "none").then(|| arg.parse().unwrap())
+    }
+}
+
+const AIR: u8 = 0;
+const ROCK: u8 = 1;
+const SAND: u8 = 2;
+
+/// What happened to a dropped grain.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Fall {
+    Rest(Cell),
+    Abyss,
+    Blocked,
 }
 
-fn drop<F: Fn(&Cell) -> bool>(grid: &Grid, mut cell: Cell, test: F) -> Option<Cell> {
-    while let Some(next) = cell.next().into_iter().find(|next| grid.is_empty(next)) {
-        if !test(&next) {
-            return None;
+/// Dense cave wide enough for the pyramids that pile up on the floor. The
+/// fall path of the last grain from each source is kept: the next grain
+/// follows the same cells, so it resumes from the last one that is still open.
+struct Cave {
+    min: Cell,
+    rows: usize,
+    cols: usize,
+    cells: Vec<u8>,
+    /// Row of the floor, or the lowest rock when there is none.
+    floor: isize,
+    solid: bool,
+    paths: Vec<Vec<Cell>>,
+    sand: usize,
+}
+
+impl Cave {
+    fn new(grid: &Grid, setup: &Setup) -> Self {
+        let rows = bounds(grid.rock.iter().map(|cell| cell.row));
+        let cols = bounds(grid.rock.iter().map(|cell| cell.col));
+        let floor = rows.1 + setup.floor.unwrap_or_default();
+        let top = setup
+            .sources
+            .iter()
+            .map(|src| src.row)
+            .fold(rows.0, isize::min);
+        // A source can sit below the floor or the abyss line, and the cells
+        // under it are still looked at.
+        let bottom = setup
+            .sources
+            .iter()
+            .map(|src| src.row)
+            .fold(floor, isize::max);
+        let (left, right) = setup.sources.iter().fold(cols, |acc, src| {
+            let height = if setup.floor.is_some() {
+                (floor - src.row).max(0)
+            } else {
+                0
+            };
+            (acc.0.min(src.col - height), acc.1.max(src.col + height))
+        });
+
+        let min = Cell::at(top, left - 1);
+        let max = Cell::at(bottom + 1, right + 1);
+        let rows = (max.row - min.row + 1) as usize;
+        let cols = (max.col - min.col + 1) as usize;
+
+        let mut cave = Self {
+            min,
+            rows,
+            cols,
+            cells: vec![AIR; rows * cols],
+            floor,
+            solid: setup.floor.is_some(),
+            paths: setup.sources.iter().map(|src| vec![*src]).collect(),
+            sand: 0,
+        };
+        for cell in grid.rock.iter() {
+            let idx = cave.idx(cell);
+            cave.cells[idx] = ROCK;
+        }
+        cave
+    }
+
+    fn idx(&self, cell: &Cell) -> usize {
+        (cell.row - self.min.row) as usize * self.cols + (cell.col - self.min.col) as usize
+    }
+
+    fn is_empty(&self, cell: &Cell) -> bool {
+        !(self.solid && cell.row >= self.floor) && self.cells[self.idx(cell)] == AIR
+    }
+
+    /// Drops one grain from source `src`. Cells filled from other sources are
+    /// popped off the remembered path first; they can only be at its top.
+    fn drop(&mut self, src: usize) -> Fall {
+        while let Some(cell) = self.paths[src].last() {
+            if self.is_empty(cell) {
+                break;
+            }
+            self.paths[src].pop();
+        }
+        loop {
+            let Some(cell) = self.paths[src].last().copied() else {
+                return Fall::Blocked;
+            };
+            match cell.next().into_iter().find(|next| self.is_empty(next)) {
+                Some(next) if !self.solid && next.row > self.floor => return Fall::Abyss,
+                Some(next) => self.paths[src].push(next),
+                None => {
+                    let idx = self.idx(&cell);
+                    self.cells[idx] = SAND;
+                    self.sand += 1;
+                    self.paths[src].pop();
+                    return Fall::Rest(cell);
+                }
+            }
+        }
+    }
+
+    /// Runs until `stop`, calling `observe` with the number of grains dropped
+    /// so far after each of them.
+    fn run<F: FnMut(&Cave, usize)>(&mut self, stop: Stop, mut observe: F) {
+        let mut dropped = 0;
+        let mut spent = vec![false; self.paths.len()];
+        while spent.iter().any(|spent| !spent) {
+            for (src, spent) in spent.iter_mut().enumerate() {
+                if stop == Stop::Grains(dropped) {
+                    return;
+                }
+                if *spent {
+                    continue;
+                }
+                match self.drop(src) {
+                    Fall::Rest(_) => dropped += 1,
+                    Fall::Abyss if stop == Stop::Abyss => return,
+                    Fall::Abyss => {
+                        dropped += 1;
+                        *spent = true;
+                    }
+                    Fall::Blocked => {
+                        *spent = true;
+                        continue;
+                    }
+                }
+                observe(self, dropped);
+            }
         }
-        cell = next;
     }
 
-    Some(cell)
+    /// Where the next grain from each source resumes its fall.
+    fn falling(&self) -> Vec<Cell> {
+        self.paths
+            .iter()
+            .filter(|path| path.len() > 1)
+            .filter_map(|path| path.last().copied())
+            .collect()
+    }
+
+    fn sand_cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        (0..self.rows * self.cols)
+            .filter(|idx| self.cells[*idx] == SAND)
+            .map(|idx| {
+                Cell::at(
+                    self.min.row + (idx / self.cols) as isize,
+                    self.min.col + (idx % self.cols) as isize,
+                )
+            })
+    }
 }
 
 struct Grid {
@@ -59,7 +304,6 @@ impl Grid {
                 let dots = parse(&line);
                 dots.iter()
                     .zip(dots.iter().skip(1))
-                    .into_iter()
                     .flat_map(|(a, b)| unfold(a, b))
                     .collect::<Vec<_>>()
             })
@@ -72,13 +316,17 @@ impl Grid {
         }
     }
 
+    #[cfg(test)]
     fn bounds(&self, src: &Cell) -> (Cell, Cell) {
+        use std::iter::once;
+
         let rows = bounds(self.rock.iter().chain(once(src)).map(|cell| cell.row));
         let cols = bounds(self.rock.iter().chain(once(src)).map(|cell| cell.col));
 
         (Cell::at(rows.0, cols.0), Cell::at(rows.1, cols.1))
     }
 
+    #[cfg(test)]
     fn is_empty(&self, cell: &Cell) -> bool {
         !self
             .floor
@@ -89,31 +337,36 @@ impl Grid {
             && !self.sand.contains(cell)
     }
 
-    fn dump(&self) -> String {
-        let rows = bounds(
-            self.rock
-                .iter()
-                .chain(self.sand.iter())
-                .map(|cell| cell.row),
-        );
-        let cols = bounds(
-            self.rock
-                .iter()
-                .chain(self.sand.iter())
-                .map(|cell| cell.col),
-        );
+    /// Draws rock and sand as `#` and `o`, plus the floor, sources as `+` and
+    /// grains in flight as `~`. Without a window the picture spans everything
+    /// drawn, bounds included.
+    fn dump(&self, view: &View) -> String {
+        let (min, max) = view.window.unwrap_or_else(|| {
+            let cells = || {
+                self.rock
+                    .iter()
+                    .chain(self.sand.iter())
+                    .chain(view.sources.iter())
+                    .chain(view.falling.iter())
+            };
+            let rows = bounds(cells().map(|cell| cell.row).chain(self.floor));
+            let cols = bounds(cells().map(|cell| cell.col));
+            (Cell::at(rows.0, cols.0), Cell::at(rows.1, cols.1))
+        });
 
-        (rows.0..=rows.1)
-            .into_iter()
-            .map(move |row| {
-                (cols.0..cols.1)
-                    .into_iter()
-                    .map(move |col| {
+        (min.row..=max.row)
+            .map(|row| {
+                (min.col..=max.col)
+                    .map(|col| {
                         let cell = Cell::at(row, col);
-                        if self.rock.contains(&cell) {
+                        if self.rock.contains(&cell) || self.floor == Some(row) {
                             '#'
                         } else if self.sand.contains(&cell) {
                             'o'
+                        } else if view.falling.contains(&cell) {
+                            '~'
+                        } else if view.sources.contains(&cell) {
+                            '+'
                         } else {
                             '.'
                         }
@@ -125,6 +378,15 @@ impl Grid {
     }
 }
 
+/// What `Grid::dump` draws on top of the cave, and an optional inclusive
+/// `(top left, bottom right)` crop.
+#[derive(Debug, Default, Clone)]
+struct View {
+    sources: Vec<Cell>,
+    falling: Vec<Cell>,
+    window: Option<(Cell, Cell)>,
+}
+
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
 struct Cell {
     row: isize,
@@ -153,6 +415,7 @@ impl Cell {
         ]
     }
 
+    #[cfg(test)]
     fn fits(&self, min: &Cell, max: &Cell) -> bool {
         self.row >= min.row && self.row <= max.row && self.col >= min.col && self.col <= max.col
     }
@@ -160,7 +423,6 @@ impl Cell {
 
 fn parse(line: &str) -> Vec<Cell> {
     line.split(" -> ")
-        .into_iter()
         .map(|cell| {
             let mut it = cell.split(',');
             let col = it.next().unwrap().parse().unwrap();
@@ -173,7 +435,7 @@ fn parse(line: &str) -> Vec<Cell> {
 fn seq(a: isize, b: isize) -> impl Iterator<Item = isize> {
     let len = b - a;
     let dir = len / len.abs();
-    (0..=len.abs()).into_iter().map(move |i| a + i * dir)
+    (0..=len.abs()).map(move |i| a + i * dir)
 }
 
 fn unfold(a: &Cell, b: &Cell) -> Vec<Cell> {
@@ -198,3 +460,213 @@ fn bounds<T: Sized + Ord + Default + Clone>(it: impl Iterator<Item = T> + Clone)
     let max = it.reduce(|acc, val| acc.max(val)).unwrap_or_default();
     (min, max)
 }
+
+#[cfg(test)]
+mod day14 {
+    use super::*;
+
+    fn drop<F: Fn(&Cell) -> bool>(grid: &Grid, mut cell: Cell, test: F) -> Option<Cell> {
+        while let Some(next) = cell.next().into_iter().find(|next| grid.is_empty(next)) {
+            if !test(&next) {
+                return None;
+            }
+            cell = next;
+        }
+
+        Some(cell)
+    }
+
+    /// Reference engine: both parts on the sparse `Grid`, one grain at a time
+    /// from the source.
+    fn slow(grid: &mut Grid, src: Cell) -> (usize, usize) {
+        let (min, max) = grid.bounds(&src);
+        while let Some(cell) = drop(grid, src, |next| next.fits(&min, &max)) {
+            grid.sand.insert(cell);
+        }
+        let part1 = grid.sand.len();
+
+        grid.floor = Some(max.row + 2);
+        while let Some(cell) = drop(grid, src, |_| true) {
+            grid.sand.insert(cell);
+            if cell == src {
+                break;
+            }
+        }
+        (part1, grid.sand.len())
+    }
+
+    fn fast(grid: &Grid, src: Cell) -> (usize, usize) {
+        let sand = |setup: Setup| {
+            let mut cave = Cave::new(grid, &setup);
+            cave.run(setup.stop, |_, _| {});
+            cave.sand
+        };
+        (sand(Setup::part1(src)), sand(Setup::part2(src)))
+    }
+
+    const EXAMPLE: &str = "498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9";
+
+    fn example() -> Grid {
+        Grid::from_lines(EXAMPLE.lines().map(String::from).collect())
+    }
+
+    #[test]
+    fn test_example() {
+        let src = Cell::at(0, 500);
+        assert_eq!(fast(&example(), src), (24, 93));
+        assert_eq!(slow(&mut example(), src), (24, 93));
+    }
+
+    /// Round-robin reference on the sparse grid with a floor.
+    fn reference(sources: &[Cell], depth: isize) -> usize {
+        let mut grid = example();
+        let (_, max) = grid.bounds(&sources[0]);
+        grid.floor = Some(max.row + depth);
+        let mut blocked = vec![false; sources.len()];
+        while blocked.iter().any(|b| !b) {
+            for (src, blocked) in sources.iter().zip(blocked.iter_mut()) {
+                if *blocked || grid.sand.contains(src) {
+                    *blocked = true;
+                    continue;
+                }
+                let cell = drop(&grid, *src, |_| true).unwrap();
+                grid.sand.insert(cell);
+            }
+        }
+        grid.sand.len()
+    }
+
+    #[test]
+    fn test_setups() {
+        let grid = example();
+        let run = |setup: Setup| {
+            let mut cave = Cave::new(&grid, &setup);
+            cave.run(setup.stop, |_, _| {});
+            cave.sand
+        };
+
+        let src = Cell::at(0, 500);
+        let sources = vec![Cell::at(0, 500), Cell::at(2, 490), Cell::at(1, 503)];
+        assert_eq!(
+            run(Setup {
+                sources: sources.clone(),
+                floor: Some(2),
+                stop: Stop::Blocked,
+            }),
+            reference(&sources, 2)
+        );
+        assert_eq!(
+            run(Setup {
+                sources: vec![src],
+                floor: Some(5),
+                stop: Stop::Blocked,
+            }),
+            reference(&[src], 5)
+        );
+        assert_eq!(
+            run(Setup {
+                stop: Stop::Grains(10),
+                ..Setup::part2(src)
+            }),
+            10
+        );
+        // Without a floor the right-hand source only leaks.
+        assert_eq!(
+            run(Setup {
+                sources: vec![src, Cell::at(0, 510)],
+                floor: None,
+                stop: Stop::Blocked,
+            }),
+            24
+        );
+        // Sources below the lowest rock or the floor.
+        assert_eq!(
+            run(Setup {
+                sources: vec![Cell::at(20, 500)],
+                floor: None,
+                stop: Stop::Blocked,
+            }),
+            0
+        );
+        assert_eq!(
+            run(Setup {
+                sources: vec![Cell::at(20, 500), src],
+                floor: Some(2),
+                stop: Stop::Blocked,
+            }),
+            93
+        );
+        assert_eq!(Stop::parse("abyss"), Stop::Abyss);
+        assert_eq!(Stop::parse("42"), Stop::Grains(42));
+        assert_eq!(Setup::floor("none"), None);
+    }
+
+    #[test]
+    fn test_dump() {
+        let src = Cell::at(0, 500);
+        let mut grid = example();
+        let view = View {
+            sources: vec![src],
+            ..View::default()
+        };
+        assert_eq!(
+            grid.dump(&view),
+            [
+                "......+...",
+                "..........",
+                "..........",
+                "..........",
+                "....#...##",
+                "....#...#.",
+                "..###...#.",
+                "........#.",
+                "........#.",
+                "#########.",
+            ]
+            .join("\n")
+        );
+
+        let setup = Setup::part1(src);
+        let mut cave = Cave::new(&grid, &setup);
+        let mut frames = vec![];
+        cave.run(setup.stop, |cave, dropped| {
+            if dropped == 5 {
+                frames.push(cave.falling());
+            }
+        });
+        assert_eq!(frames, vec![vec![Cell::at(7, 499)]]);
+
+        grid.sand = cave.sand_cells().collect();
+        assert_eq!(
+            grid.dump(&view),
+            [
+                "......+...",
+                "..........",
+                "......o...",
+                ".....ooo..",
+                "....#ooo##",
+                "...o#ooo#.",
+                "..###ooo#.",
+                "....oooo#.",
+                ".o.ooooo#.",
+                "#########.",
+            ]
+            .join("\n")
+        );
+
+        grid.floor = Some(11);
+        let view = View {
+            sources: vec![src],
+            falling: vec![Cell::at(1, 500)],
+            window: Some((Cell::at(0, 498), Cell::at(2, 502))),
+        };
+        assert_eq!(grid.dump(&view), ["..+..", "..~..", "..o.."].join("\n"));
+        grid.sand.clear();
+        let view = View {
+            window: Some((Cell::at(9, 502), Cell::at(11, 504))),
+            ..view
+        };
+        assert_eq!(grid.dump(&view), ["#..", "...", "###"].join("\n"));
+    }
+}
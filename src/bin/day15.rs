@@ -1,36 +1,127 @@
-use std::collections::HashSet;
+use std::env::args;
 
 use advent_of_code_2022::lines;
 
 fn main() {
-    let all: Vec<(Sensor, Beacon)> = lines().into_iter().map(|line| parse(&line)).collect();
+    let opts = args().skip(1).collect::<Vec<_>>();
+    let opt = |name: &str| {
+        opts.iter()
+            .position(|opt| opt == name)
+            .and_then(|idx| opts.get(idx + 1))
+            .map(|arg| arg.parse::<isize>().unwrap())
+    };
+    let flag = |name: &str| opts.iter().any(|opt| opt == name);
+    let all = parse_all(&lines()).unwrap_or_else(|e| panic!("{}", e));
 
-    let (min, max) = all
-        .iter()
-        .map(|(sensor, beacon)| bound(sensor, beacon))
-        .reduce(|acc, (min, max)| {
-            let min = Dot::of(acc.0.x.min(min.x), acc.0.y.min(min.y));
-            let max = Dot::of(acc.1.x.max(max.x), acc.1.y.max(max.y));
-            (min, max)
-        })
-        .unwrap();
+    let row = opt("--row").unwrap_or(ROW);
+    let max = opt("--max").unwrap_or(MAX);
 
-    let y = 2000000;
-    let part1 = (min.x..=max.x)
-        .into_iter()
-        .map(|x| Dot::of(x, y))
-        .filter(|dot| hits(dot, &all))
-        .count();
+    let part1 = count(&all, row);
     println!("{}", part1);
 
-    let part2 = all
-        .iter()
-        .flat_map(|(sensor, beacon)| frontier(sensor, beacon))
-        .filter(|dot| dot.x >= 0 && dot.x <= 4000000 && dot.y >= 0 && dot.y <= 4000000)
-        .find(|dot| !hits(dot, &all))
-        .map(|dot| dot.x * 4000000 + dot.y)
+    let part2 = solve(&all, max)
+        .or_else(|| uncovered(&all, max).first().copied())
+        .map(|dot| tuning(&dot))
         .unwrap();
     println!("{:?}", part2);
+
+    if flag("--all") {
+        for dot in uncovered(&all, max) {
+            println!("{},{}", dot.x, dot.y);
+        }
+    }
+}
+
+/// Row scanned in part 1 and the side of the square searched in part 2 for
+/// the real input; the example uses 10 and 20.
+const ROW: isize = 2000000;
+const MAX: isize = 4000000;
+
+/// Positions on row `y` where the beacon cannot be.
+fn count(all: &[(Sensor, Beacon)], y: isize) -> usize {
+    coverage(all, y).iter().map(Range::len).sum()
+}
+
+/// Sensor position and range on the rotated grid u = x + y, v = x - y, where
+/// the diamond becomes the square `|u - us| <= r && |v - vs| <= r`.
+fn rotated(all: &[(Sensor, Beacon)]) -> Vec<(isize, isize, isize)> {
+    all.iter()
+        .map(|(sensor, beacon)| {
+            let r = sensor.0.len(&beacon.0);
+            (sensor.0.x + sensor.0.y, sensor.0.x - sensor.0.y, r)
+        })
+        .collect()
+}
+
+/// The only spot in `0..=max` square not covered by any sensor, trying only
+/// crossings of the lines running just outside each pair of diamonds: a lone
+/// gap away from the square's edge must sit on such a crossing.
+fn solve(all: &[(Sensor, Beacon)], max: isize) -> Option<Dot> {
+    let squares = rotated(all);
+    let us = squares
+        .iter()
+        .flat_map(|&(u, _, r)| [u - r - 1, u + r + 1])
+        .collect::<Vec<_>>();
+    let vs = squares
+        .iter()
+        .flat_map(|&(_, v, r)| [v - r - 1, v + r + 1])
+        .collect::<Vec<_>>();
+
+    us.iter()
+        .flat_map(|&u| vs.iter().map(move |&v| (u, v)))
+        .filter(|(u, v)| (u + v) % 2 == 0)
+        .map(|(u, v)| Dot::of((u + v) / 2, (u - v) / 2))
+        .filter(|dot| dot.x >= 0 && dot.x <= max && dot.y >= 0 && dot.y <= max)
+        .find(|dot| !reached(dot, all))
+}
+
+/// Every spot in `0..=max` square out of reach of all sensors. On the rotated
+/// grid the square edges cut the plane into cells that are either wholly
+/// covered or wholly free, so only one corner per cell is checked.
+fn uncovered(all: &[(Sensor, Beacon)], max: isize) -> Vec<Dot> {
+    let squares = rotated(all);
+    let cuts = |lo: isize, hi: isize, at: fn(&(isize, isize, isize)) -> isize| {
+        let mut cuts = squares
+            .iter()
+            .flat_map(|sq| [at(sq) - sq.2, at(sq) + sq.2 + 1])
+            .filter(|&c| c > lo && c <= hi)
+            .chain([lo, hi + 1])
+            .collect::<Vec<_>>();
+        cuts.sort();
+        cuts.dedup();
+        cuts
+    };
+    let us = cuts(0, 2 * max, |sq| sq.0);
+    let vs = cuts(-max, max, |sq| sq.1);
+
+    let mut out = Vec::new();
+    for u in us.windows(2) {
+        for v in vs.windows(2) {
+            let covered = squares
+                .iter()
+                .any(|&(su, sv, r)| (u[0] - su).abs() <= r && (v[0] - sv).abs() <= r);
+            if covered {
+                continue;
+            }
+            // Clip the cell to the x, y square, a diamond on this grid.
+            let lo = u[0].max(v[0]).max(1 - v[1]);
+            let hi = (u[1] - 1).min(2 * max - v[0]).min(v[1] - 1 + 2 * max);
+            for u in lo..=hi {
+                let lo = v[0].max(-u).max(u - 2 * max);
+                let hi = (v[1] - 1).min(2 * max - u).min(u);
+                let lo = lo + (u + lo).rem_euclid(2);
+                for v in (lo..=hi).step_by(2) {
+                    out.push(Dot::of((u + v) / 2, (u - v) / 2));
+                }
+            }
+        }
+    }
+    out.sort_by_key(|dot| (dot.y, dot.x));
+    out
+}
+
+fn tuning(dot: &Dot) -> isize {
+    dot.x * 4000000 + dot.y
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
@@ -49,12 +140,77 @@ impl Dot {
     }
 }
 
-fn hits(dot: &Dot, all: &[(Sensor, Beacon)]) -> bool {
-    all.iter().all(|(_, beacon)| &beacon.0 != dot)
-        && all.iter().any(|(sensor, beacon)| {
-            let len = sensor.0.len(&beacon.0);
-            sensor.0.len(dot) <= len
+/// Within range of some sensor, known beacons included.
+fn reached(dot: &Dot, all: &[(Sensor, Beacon)]) -> bool {
+    all.iter().any(|(sensor, beacon)| {
+        let len = sensor.0.len(&beacon.0);
+        sensor.0.len(dot) <= len
+    })
+}
+
+/// Inclusive span of x values on a single row.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct Range {
+    lo: isize,
+    hi: isize,
+}
+
+impl Range {
+    fn of(lo: isize, hi: isize) -> Self {
+        Self { lo, hi }
+    }
+
+    fn len(&self) -> usize {
+        (self.hi - self.lo + 1) as usize
+    }
+}
+
+/// Sorted, disjoint spans on row `y` within reach of some sensor.
+fn reach(all: &[(Sensor, Beacon)], y: isize) -> Vec<Range> {
+    let mut spans = all
+        .iter()
+        .filter_map(|(sensor, beacon)| {
+            let len = sensor.0.len(&beacon.0) - (sensor.0.y - y).abs();
+            (len >= 0).then(|| Range::of(sensor.0.x - len, sensor.0.x + len))
         })
+        .collect::<Vec<_>>();
+    spans.sort_by_key(|range| range.lo);
+
+    let mut merged: Vec<Range> = Vec::with_capacity(spans.len());
+    for range in spans {
+        match merged.last_mut() {
+            Some(last) if range.lo <= last.hi + 1 => last.hi = last.hi.max(range.hi),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Spans on row `y` where a beacon cannot be: `reach` minus the known beacons.
+fn coverage(all: &[(Sensor, Beacon)], y: isize) -> Vec<Range> {
+    let mut beacons = all
+        .iter()
+        .map(|(_, beacon)| beacon.0)
+        .filter(|dot| dot.y == y)
+        .map(|dot| dot.x)
+        .collect::<Vec<_>>();
+    beacons.sort();
+    beacons.dedup();
+
+    let mut out = Vec::new();
+    for range in reach(all, y) {
+        let mut lo = range.lo;
+        for &x in beacons.iter().filter(|&&x| x >= range.lo && x <= range.hi) {
+            if x > lo {
+                out.push(Range::of(lo, x - 1));
+            }
+            lo = x + 1;
+        }
+        if lo <= range.hi {
+            out.push(Range::of(lo, range.hi));
+        }
+    }
+    out
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -63,76 +219,137 @@ struct Sensor(Dot);
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 struct Beacon(Dot);
 
-fn bound(sensor: &Sensor, beacon: &Beacon) -> (Dot, Dot) {
-    let len = sensor.0.len(&beacon.0);
-    let min = Dot::of(sensor.0.x - len, sensor.0.y - len);
-    let max = Dot::of(sensor.0.x + len, sensor.0.y + len);
-    (min, max)
-}
-
-fn frontier(sensor: &Sensor, beacon: &Beacon) -> HashSet<Dot> {
-    let (min, max) = bound(sensor, beacon);
-    let east = Dot::of(min.x - 1, sensor.0.y);
-    let west = Dot::of(max.x + 1, sensor.0.y);
-    let north = Dot::of(sensor.0.x, min.y - 1);
-    let south = Dot::of(sensor.0.x, max.y + 1);
-
-    vec![
-        diagonal(&east, &north),
-        diagonal(&north, &west),
-        diagonal(&west, &south),
-        diagonal(&south, &east),
-    ]
-    .into_iter()
-    .flatten()
-    .collect()
-}
-
-fn diagonal(a: &Dot, b: &Dot) -> Vec<Dot> {
-    assert!((a.x - b.x).abs() == (a.y - b.y).abs());
-    let len = (a.x - b.x).abs();
-    let dx = (b.x - a.x) / len;
-    let dy = (b.y - a.y) / len;
-    (0..len)
-        .into_iter()
-        .map(|i| Dot::of(a.x + i * dx, a.y + i * dy))
+/// Parses all lines, failing on the first malformed one with its 1-based number.
+fn parse_all(lines: &[String]) -> Result<Vec<(Sensor, Beacon)>, String> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| parse(line).map_err(|e| format!("line {}: {}", idx + 1, e)))
         .collect()
 }
 
-fn parse(line: &str) -> (Sensor, Beacon) {
-    let parsed = line
-        .split_ascii_whitespace()
-        .into_iter()
-        .filter(|line| line.starts_with("x=") || line.starts_with("y="))
-        .map(|line| {
-            line.chars()
-                .filter(|c| c.is_ascii_digit())
-                .collect::<String>()
-        })
-        .map(|num| num.parse::<isize>().unwrap())
-        .collect::<Vec<_>>();
+/// `Sensor at x=<int>, y=<int>: closest beacon is at x=<int>, y=<int>`
+fn parse(line: &str) -> Result<(Sensor, Beacon), String> {
+    let rest = line
+        .strip_prefix("Sensor at ")
+        .ok_or_else(|| format!("expected 'Sensor at ' in '{}'", line))?;
+    let (sensor, beacon) = rest
+        .split_once(": closest beacon is at ")
+        .ok_or_else(|| format!("expected ': closest beacon is at ' in '{}'", line))?;
+    Ok((Sensor(parse_dot(sensor)?), Beacon(parse_dot(beacon)?)))
+}
 
-    (
-        Sensor(Dot {
-            x: parsed[0],
-            y: parsed[1],
-        }),
-        Beacon(Dot {
-            x: parsed[2],
-            y: parsed[3],
-        }),
-    )
+fn parse_dot(text: &str) -> Result<Dot, String> {
+    let (x, y) = text
+        .split_once(", ")
+        .ok_or_else(|| format!("expected 'x=<int>, y=<int>', got '{}'", text))?;
+    let num = |text: &str, key: &str| {
+        text.strip_prefix(key)
+            .ok_or_else(|| format!("expected '{}' in '{}'", key, text))?
+            .parse::<isize>()
+            .map_err(|e| format!("bad number in '{}': {}", text, e))
+    };
+    Ok(Dot::of(num(x, "x=")?, num(y, "y=")?))
 }
 
 #[cfg(test)]
 mod day15 {
+    use std::collections::HashSet;
+
     use super::*;
 
+    /// Reference: walks every spot just outside each sensor's range.
+    fn find(all: &[(Sensor, Beacon)], max: isize) -> Dot {
+        all.iter()
+            .flat_map(|(sensor, beacon)| frontier(sensor, beacon))
+            .filter(|dot| dot.x >= 0 && dot.x <= max && dot.y >= 0 && dot.y <= max)
+            .find(|dot| !reached(dot, all))
+            .unwrap()
+    }
+
+    fn bound(sensor: &Sensor, beacon: &Beacon) -> (Dot, Dot) {
+        let len = sensor.0.len(&beacon.0);
+        let min = Dot::of(sensor.0.x - len, sensor.0.y - len);
+        let max = Dot::of(sensor.0.x + len, sensor.0.y + len);
+        (min, max)
+    }
+
+    fn frontier(sensor: &Sensor, beacon: &Beacon) -> HashSet<Dot> {
+        let (min, max) = bound(sensor, beacon);
+        let east = Dot::of(min.x - 1, sensor.0.y);
+        let west = Dot::of(max.x + 1, sensor.0.y);
+        let north = Dot::of(sensor.0.x, min.y - 1);
+        let south = Dot::of(sensor.0.x, max.y + 1);
+
+        vec![
+            diagonal(&east, &north),
+            diagonal(&north, &west),
+            diagonal(&west, &south),
+            diagonal(&south, &east),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn diagonal(a: &Dot, b: &Dot) -> Vec<Dot> {
+        assert!((a.x - b.x).abs() == (a.y - b.y).abs());
+        let len = (a.x - b.x).abs();
+        let dx = (b.x - a.x) / len;
+        let dy = (b.y - a.y) / len;
+        (0..len)
+            .map(|i| Dot::of(a.x + i * dx, a.y + i * dy))
+            .collect()
+    }
+
+    const EXAMPLE: &str = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3";
+
+    #[test]
+    fn test_example() {
+        let all = EXAMPLE
+            .lines()
+            .map(|line| parse(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(count(&all, 10), 26);
+        assert_eq!(find(&all, 20), Dot::of(14, 11));
+        assert_eq!(tuning(&find(&all, 20)), 56000011);
+        assert_eq!(solve(&all, 20), Some(Dot::of(14, 11)));
+        assert_eq!(uncovered(&all, 20), vec![Dot::of(14, 11)]);
+    }
+
+    #[test]
+    fn test_uncovered() {
+        // Sensor at the centre of a 0..=4 square reaching distance 2 leaves
+        // the four corners free, three spots each.
+        let all = vec![parse("Sensor at x=2, y=2: closest beacon is at x=2, y=4").unwrap()];
+        let free = uncovered(&all, 4);
+        assert_eq!(free.len(), 12);
+        assert!(free.contains(&Dot::of(0, 0)));
+        assert!(free.contains(&Dot::of(4, 3)));
+        assert!(!free.contains(&Dot::of(2, 0)));
+        assert!(free.iter().all(|dot| !reached(dot, &all)));
+        // Gaps held in place by the square's edge aren't on any crossing.
+        assert_eq!(solve(&all, 4), None);
+    }
+
     #[test]
     fn test_parse() {
         assert_eq!(
             parse("Sensor at x=2389280, y=2368338: closest beacon is at x=2127703, y=2732666"),
-            (
+            Ok((
                 Sensor(Dot {
                     x: 2389280,
                     y: 2368338
@@ -141,7 +358,42 @@ mod day15 {
                     x: 2127703,
                     y: 2732666
                 })
-            )
+            ))
+        );
+        assert_eq!(
+            parse("Sensor at x=-5, y=0: closest beacon is at x=3, y=-12"),
+            Ok((Sensor(Dot::of(-5, 0)), Beacon(Dot::of(3, -12))))
+        );
+        assert_eq!(
+            parse("Sensor at x=+7, y=-0: closest beacon is at x=-1000000, y=1"),
+            Ok((Sensor(Dot::of(7, 0)), Beacon(Dot::of(-1000000, 1))))
         );
+
+        assert!(parse("Sensor at x=1, y=2").is_err());
+        assert!(parse("Sensor at x=1 y=2: closest beacon is at x=3, y=4").is_err());
+        assert!(parse("Sensor at x=--1, y=2: closest beacon is at x=3, y=4").is_err());
+        assert!(parse("Beacon at x=1, y=2: closest beacon is at x=3, y=4").is_err());
+
+        let lines = [
+            "Sensor at x=1, y=2: closest beacon is at x=3, y=4",
+            "Sensor at x=1, y=2: closest beacon is at x=3, y=four",
+        ]
+        .map(String::from);
+        let err = parse_all(&lines).unwrap_err();
+        assert!(err.starts_with("line 2: "), "{}", err);
+        assert!(parse_all(&lines[..1]).is_ok());
+    }
+
+    #[test]
+    fn test_coverage() {
+        let all = vec![
+            parse("Sensor at x=8, y=7: closest beacon is at x=2, y=10").unwrap(),
+            parse("Sensor at x=12, y=14: closest beacon is at x=10, y=16").unwrap(),
+        ];
+        assert_eq!(reach(&all, 10), vec![Range::of(2, 14)]);
+        assert_eq!(coverage(&all, 10), vec![Range::of(3, 14)]);
+        assert_eq!(reach(&all, 16), vec![Range::of(8, 8), Range::of(10, 14)]);
+        assert_eq!(coverage(&all, 16), vec![Range::of(8, 8), Range::of(11, 14)]);
+        assert_eq!(coverage(&all, 40), vec![]);
     }
 }
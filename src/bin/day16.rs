@@ -43,8 +43,8 @@ struct Valve {
 
 fn index(valves: &HashMap<Name, Valve>) -> HashMap<Name, HashMap<Name, isize>> {
     valves
-        .iter()
-        .map(|(name, _)| {
+        .keys()
+        .map(|name| {
             let dist = scan(valves, name)
                 .into_iter()
                 .filter(|(n, _)| valves[n].rate > 0)
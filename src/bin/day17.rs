@@ -1,8 +1,4 @@
-use std::{
-    collections::{HashMap, HashSet},
-    ops::Add,
-    time::Instant,
-};
+use std::{collections::HashMap, env::args, time::Instant};
 
 use advent_of_code_2022::lines;
 
@@ -25,7 +21,10 @@ const PIECES: &str = "####
 ##
 ";
 
+const ROWS: usize = 20;
+
 fn main() {
+    let opt = args().nth(1).unwrap_or_default();
     let at = Instant::now();
     let steam = &lines()[0];
 
@@ -37,11 +36,17 @@ fn main() {
         .collect::<Vec<_>>();
 
     let mut screen = Screen::new(7, steam.chars().collect());
+    if opt == "--frames" {
+        screen.frames = Some(ROWS);
+    }
     for i in 0..2022 {
         let piece = pieces[i % pieces.len()].clone();
         screen.piece(piece);
     }
 
+    if opt == "--dump" {
+        println!("{}\n", screen.dump(ROWS, None));
+    }
     let part1 = screen.max();
     println!("{} ({} ms)", part1, at.elapsed().as_millis());
 
@@ -81,108 +86,89 @@ fn main() {
     println!("{} ({} ms)", part2, at.elapsed().as_millis());
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-struct Dot {
-    x: isize,
-    y: isize,
-}
-
-impl Dot {
-    fn of(x: isize, y: isize) -> Self {
-        Self { x, y }
-    }
-}
-
-impl Add<Dot> for Dot {
-    type Output = Dot;
-
-    fn add(self, that: Dot) -> Self::Output {
-        Dot::of(self.x + that.x, self.y + that.y)
-    }
-}
-
 #[derive(Debug, Clone)]
 struct Piece {
-    height: isize,
-    dots: Vec<Dot>,
+    rows: Vec<u8>,
 }
 
 impl Piece {
     fn parse(lines: &[&str]) -> Self {
-        let dots: Vec<Dot> = lines
+        let rows = lines
             .iter()
-            .enumerate()
             .rev()
-            .flat_map(|(y, row)| {
+            .map(|row| {
                 row.chars()
-                    .into_iter()
                     .enumerate()
                     .filter(|(_, c)| c == &'#')
-                    .map(move |(x, _)| Dot::of(x as isize, -(y as isize)))
+                    .fold(0u8, |acc, (x, _)| acc | 1 << x)
             })
             .collect();
-
-        let min = dots
-            .iter()
-            .cloned()
-            .reduce(|acc, dot| Dot::of(acc.x.min(dot.x), acc.y.min(dot.y)))
-            .unwrap();
-        let max = dots
-            .iter()
-            .cloned()
-            .reduce(|acc, dot| Dot::of(acc.x.max(dot.x), acc.y.max(dot.y)))
-            .unwrap();
-
-        Self {
-            // width: max.x - min.x + 1,
-            height: max.y - min.y + 1,
-            dots,
-        }
-    }
-
-    fn dots(&self) -> Vec<Dot> {
-        self.dots.clone()
-    }
-
-    fn add(&mut self, one: &Dot) {
-        self.dots.iter_mut().for_each(|dot| *dot = *dot + *one);
+        Self { rows }
     }
 
-    fn lshift(&mut self) {
-        let one = Dot::of(-1, 0);
-        self.add(&one);
+    fn height(&self) -> usize {
+        self.rows.len()
     }
 
-    fn rshift(&mut self) {
-        let one = Dot::of(1, 0);
-        self.add(&one);
+    fn lshift(&self) -> Option<Self> {
+        if self.rows.iter().any(|row| row & 1 != 0) {
+            return None;
+        }
+        let rows = self.rows.iter().map(|row| row >> 1).collect();
+        Some(Self { rows })
     }
 
-    fn down(&mut self) {
-        let one = Dot::of(0, -1);
-        self.add(&one);
+    fn rshift(&self, len: usize) -> Option<Self> {
+        if self.rows.iter().any(|row| row & 1 << (len - 1) != 0) {
+            return None;
+        }
+        let rows = self.rows.iter().map(|row| row << 1).collect();
+        Some(Self { rows })
     }
 }
 
+/// Chamber as a stack of row bitmasks (bit `x` is column `x`), where
+/// `rows[0]` is the row at height `base`: everything below the highest
+/// fully blocked row is unreachable and gets pruned away.
 struct Screen {
     len: usize,
     steam: Vec<char>,
     counter: usize,
-    cells: HashSet<Dot>,
+    rows: Vec<u8>,
+    base: usize,
+    height: usize,
+    tops: Vec<usize>,
+    frames: Option<usize>,
 }
 
 impl Screen {
     fn new(len: usize, steam: Vec<char>) -> Self {
+        assert!(len <= 8, "chamber too wide: {}", len);
         Self {
             len,
             steam,
             counter: 0,
-            cells: HashSet::new(),
+            rows: Vec::new(),
+            base: 0,
+            height: 0,
+            tops: vec![0; len],
+            frames: None,
         }
     }
 
     fn max(&self) -> usize {
-        self.cells.iter().map(|dot| dot.y).max().unwrap_or_default() as usize
+        self.height
+    }
+
+    fn full(&self) -> u8 {
+        ((1u16 << self.len) - 1) as u8
+    }
+
+    fn row(&self, y: usize) -> u8 {
+        y.checked_sub(self.base)
+            .and_then(|idx| self.rows.get(idx))
+            .copied()
+            .unwrap_or_default()
     }
 
     fn blow(&mut self) -> char {
@@ -191,74 +177,205 @@ impl Screen {
         c
     }
 
-    fn fits(&self, piece: &Piece) -> bool {
-        piece.dots.iter().all(|dot| {
-            dot.x >= 0 && dot.x < self.len as isize && dot.y > 0 && !self.cells.contains(dot)
-        })
+    fn fits(&self, piece: &Piece, y: usize) -> bool {
+        piece
+            .rows
+            .iter()
+            .enumerate()
+            .all(|(i, row)| self.row(y + i) & row == 0)
     }
 
-    fn piece(&mut self, mut piece: Piece) {
-        let at: Dot = Dot::of(2, self.max() as isize + piece.height + 3);
-        piece.add(&at);
+    fn piece(&mut self, piece: Piece) {
+        let mut piece = Piece {
+            rows: piece.rows.iter().map(|row| row << 2).collect(),
+        };
+        let mut y = self.height + 3;
+        if let Some(rows) = self.frames {
+            println!(
+                "The rock begins falling:\n{}\n",
+                self.dump(rows, Some((&piece, y)))
+            );
+        }
 
-        let rest = loop {
+        loop {
             let wind = self.blow();
-            if wind == '<' {
-                let mut next = piece.clone();
-                next.lshift();
-                if self.fits(&next) {
-                    piece = next;
-                }
-            } else if wind == '>' {
-                let mut next = piece.clone();
-                next.rshift();
-                if self.fits(&next) {
+            let next = match wind {
+                '<' => piece.lshift(),
+                '>' => piece.rshift(self.len),
+                _ => None,
+            };
+            let moved = match next {
+                Some(next) if self.fits(&next, y) => {
                     piece = next;
+                    true
                 }
+                _ => false,
+            };
+            if let Some(rows) = self.frames {
+                let side = if wind == '<' { "left" } else { "right" };
+                let stuck = if moved { "" } else { ", but nothing happens" };
+                println!(
+                    "Jet of gas pushes rock {}{}:\n{}\n",
+                    side,
+                    stuck,
+                    self.dump(rows, Some((&piece, y)))
+                );
             }
 
-            let mut next = piece.clone();
-            next.down();
-            if self.fits(&next) {
-                piece = next;
+            if y > 0 && self.fits(&piece, y - 1) {
+                y -= 1;
             } else {
-                break piece;
+                break;
             }
-        };
-
-        for dot in rest.dots() {
-            self.cells.insert(dot);
         }
+
+        self.land(&piece, y);
     }
 
-    fn column(&self, idx: usize) -> impl Iterator<Item = isize> + '_ {
-        self.cells
-            .iter()
-            .filter(move |dot| dot.x == idx as isize)
-            .map(|dot| dot.y)
+    fn land(&mut self, piece: &Piece, y: usize) {
+        let top = y + piece.height();
+        if top - self.base > self.rows.len() {
+            self.rows.resize(top - self.base, 0);
+        }
+        self.height = self.height.max(top);
+
+        let mut blocked = None;
+        for (i, row) in piece.rows.iter().enumerate() {
+            let idx = y + i - self.base;
+            self.rows[idx] |= row;
+            (0..self.len)
+                .filter(|x| row & 1 << x != 0)
+                .for_each(|x| self.tops[x] = self.tops[x].max(y + i + 1));
+            if self.rows[idx] == self.full() {
+                blocked = Some(y + i);
+            }
+        }
+
+        if let Some(y) = blocked {
+            self.rows.drain(..y - self.base);
+            self.base = y;
+        }
     }
 
-    fn head(&mut self) -> (isize, [isize; 7]) {
-        let mut head: [isize; 7] = Default::default();
+    /// Top `rows` rows of the chamber in the puzzle's `|..@@@..|` format,
+    /// with the falling piece (if any) drawn as `@` at height `y`.
+    fn dump(&self, rows: usize, falling: Option<(&Piece, usize)>) -> String {
+        let top = falling
+            .map(|(piece, y)| y + piece.height())
+            .unwrap_or_default()
+            .max(self.height);
+        let bottom = top.saturating_sub(rows).max(self.base);
+
+        let mut lines = (bottom..top)
+            .rev()
+            .map(|y| {
+                let piece = falling
+                    .and_then(|(piece, at)| y.checked_sub(at).and_then(|i| piece.rows.get(i)))
+                    .copied()
+                    .unwrap_or_default();
+                let row = self.row(y);
+                let cells = (0..self.len)
+                    .map(|x| {
+                        if piece & 1 << x != 0 {
+                            '@'
+                        } else if row & 1 << x != 0 {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>();
+                format!("|{}|", cells)
+            })
+            .collect::<Vec<_>>();
+
+        if bottom == 0 {
+            lines.push(format!("+{}+", "-".repeat(self.len)));
+        }
+        lines.join("\n")
+    }
 
-        let min = (0..self.len)
-            .into_iter()
-            .map(|idx| self.column(idx).max().unwrap_or_default())
-            .min()
-            .unwrap_or_default();
+    /// Column heights relative to the lowest one; unused columns stay 0.
+    fn head(&mut self) -> (isize, [isize; 8]) {
+        let mut head: [isize; 8] = Default::default();
 
-        (0..self.len)
-            .into_iter()
-            .map(|idx| (idx, self.column(idx).max().unwrap_or_default()))
-            .for_each(|(idx, max)| head[idx] = max - min);
+        let min = self.tops.iter().copied().min().unwrap_or_default();
+        self.tops
+            .iter()
+            .enumerate()
+            .for_each(|(idx, top)| head[idx] = (top - min) as isize);
 
-        (min, head)
+        (min as isize, head)
     }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 struct State {
-    head: [isize; 7],
+    head: [isize; 8],
     piece: usize,
     steam: usize,
 }
+
+#[cfg(test)]
+mod day17 {
+    use super::*;
+
+    const EXAMPLE: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+
+    #[test]
+    fn test_example() {
+        let pieces = PIECES
+            .lines()
+            .collect::<Vec<_>>()
+            .split(|line| line.is_empty())
+            .map(Piece::parse)
+            .collect::<Vec<_>>();
+
+        let mut screen = Screen::new(7, EXAMPLE.chars().collect());
+        for i in 0..2022 {
+            screen.piece(pieces[i % pieces.len()].clone());
+        }
+        assert_eq!(screen.max(), 3068);
+
+        let mut wide = Screen::new(8, EXAMPLE.chars().collect());
+        for i in 0..100 {
+            wide.piece(pieces[i % pieces.len()].clone());
+        }
+        let (min, head) = wide.head();
+        assert_eq!(
+            min as usize + head.iter().max().copied().unwrap() as usize,
+            wide.max()
+        );
+    }
+
+    #[test]
+    fn test_dump() {
+        let pieces = PIECES
+            .lines()
+            .collect::<Vec<_>>()
+            .split(|line| line.is_empty())
+            .map(Piece::parse)
+            .collect::<Vec<_>>();
+
+        let mut screen = Screen::new(7, EXAMPLE.chars().collect());
+        screen.piece(pieces[0].clone());
+        screen.piece(pieces[1].clone());
+        assert_eq!(
+            screen.dump(10, Some((&pieces[2].rshift(7).unwrap(), 7))),
+            [
+                "|...@...|",
+                "|...@...|",
+                "|.@@@...|",
+                "|.......|",
+                "|.......|",
+                "|.......|",
+                "|...#...|",
+                "|..###..|",
+                "|...#...|",
+                "|..####.|",
+                "+-------+",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -1,62 +1,367 @@
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
+    env::args,
+    fs,
     time::Instant,
 };
 
 use advent_of_code_2022::lines;
 
 fn main() {
+    let opts = args().skip(1).collect::<Vec<_>>();
+    let opt = |name: &str| {
+        opts.iter()
+            .position(|opt| opt == name)
+            .and_then(|idx| opts.get(idx + 1))
+    };
     let at = Instant::now();
     let dots = lines()
         .into_iter()
         .map(|line| Dot::parse(&line))
         .collect::<HashSet<_>>();
 
-    let part1 = dots
-        .iter()
-        .map(|dot| {
-            adj()
-                .iter()
-                .filter(|side| !dots.contains(&dot.add(side)))
-                .count()
-        })
-        .sum::<usize>();
+    let min = min(&dots).add(&Dot::of(-1, -1, -1));
+    let max = max(&dots).add(&Dot::of(1, 1, 1));
+
+    let mut voxels = Voxels::new(&dots, &min, &max);
+    let part1 = voxels.surface();
     println!("{} ({} ms)", part1, at.elapsed().as_millis());
 
     let at = Instant::now();
-    let min = min(&dots).add(&Dot::of(-1, -1, -1));
-    let max = max(&dots).add(&Dot::of(1, 1, 1));
-    let part2 = traverse(&dots, &min, &max);
+    let part2 = voxels.exterior();
     println!("{} ({} ms)", part2, at.elapsed().as_millis());
+
+    if let Some(path) = opt("--obj") {
+        let quads = mesh(&dots, &min, &max);
+        fs::write(path, to_obj(&quads)).unwrap();
+    }
+    if let Some(path) = opt("--stl") {
+        let quads = mesh(&dots, &min, &max);
+        fs::write(path, to_stl(&quads)).unwrap();
+    }
+
+    if opts.iter().any(|opt| opt == "--pockets") {
+        let report = analyse(&dots, &min, &max);
+        println!("volume: {}", report.volume);
+        println!("components: {}", report.components);
+        println!("pockets: {}", report.pockets.len());
+        for (i, pocket) in report.pockets.iter().enumerate() {
+            println!(
+                "#{}: volume={} surface={} min={:?} max={:?}",
+                i + 1,
+                pocket.volume,
+                pocket.surface,
+                pocket.min,
+                pocket.max
+            );
+        }
+        let enclosed = report.pockets.iter().map(|p| p.surface).sum::<usize>();
+        assert_eq!(part1 - part2, enclosed);
+    }
 }
 
-fn traverse(dots: &HashSet<Dot>, min: &Dot, max: &Dot) -> usize {
-    let mut count = 0;
+/// Air trapped inside the droplet, connected through faces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pocket {
+    volume: usize,
+    surface: usize,
+    min: Dot,
+    max: Dot,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Report {
+    volume: usize,
+    components: usize,
+    pockets: Vec<Pocket>,
+}
+
+const AIR: u8 = 0;
+const LAVA: u8 = 1;
+const OUTSIDE: u8 = 2;
+
+/// Dense voxel array over the `min..=max` box, `x` major and `z` minor.
+struct Voxels {
+    dims: [usize; 3],
+    strides: [usize; 3],
+    cells: Vec<u8>,
+}
+
+impl Voxels {
+    fn new(dots: &HashSet<Dot>, min: &Dot, max: &Dot) -> Self {
+        let dims = [
+            (max.x - min.x + 1) as usize,
+            (max.y - min.y + 1) as usize,
+            (max.z - min.z + 1) as usize,
+        ];
+        let strides = [dims[1] * dims[2], dims[2], 1];
+        let mut cells = vec![AIR; dims.iter().product()];
+        for dot in dots.iter().filter(|dot| fits(dot, min, max)) {
+            let idx = (dot.x - min.x) as usize * strides[0]
+                + (dot.y - min.y) as usize * strides[1]
+                + (dot.z - min.z) as usize;
+            cells[idx] = LAVA;
+        }
+        Self {
+            dims,
+            strides,
+            cells,
+        }
+    }
+
+    /// Counts the places where `solid` changes along every line of every
+    /// axis, the box edges counting as non-solid.
+    fn transitions<F: Fn(u8) -> bool>(&self, solid: F) -> usize {
+        let mut count = 0;
+        for a in 0..3 {
+            let (b, c) = ((a + 1) % 3, (a + 2) % 3);
+            for u in 0..self.dims[b] {
+                for v in 0..self.dims[c] {
+                    let base = u * self.strides[b] + v * self.strides[c];
+                    let mut prev = false;
+                    for k in 0..self.dims[a] {
+                        let cur = solid(self.cells[base + k * self.strides[a]]);
+                        count += (cur != prev) as usize;
+                        prev = cur;
+                    }
+                    count += prev as usize;
+                }
+            }
+        }
+        count
+    }
+
+    /// Surface of the lava, including faces towards enclosed air.
+    fn surface(&self) -> usize {
+        self.transitions(|cell| cell == LAVA)
+    }
+
+    /// Surface of the lava reachable from the `min` corner: everything not
+    /// flooded from outside counts as solid, so pockets don't add faces.
+    fn exterior(&mut self) -> usize {
+        if self.cells[0] == AIR {
+            self.cells[0] = OUTSIDE;
+            let mut stack = vec![[0usize; 3]];
+            while let Some(pos) = stack.pop() {
+                for a in 0..3 {
+                    for next in [pos[a].wrapping_sub(1), pos[a] + 1] {
+                        if next >= self.dims[a] {
+                            continue;
+                        }
+                        let mut pos = pos;
+                        pos[a] = next;
+                        let idx = (0..3).map(|i| pos[i] * self.strides[i]).sum::<usize>();
+                        if self.cells[idx] == AIR {
+                            self.cells[idx] = OUTSIDE;
+                            stack.push(pos);
+                        }
+                    }
+                }
+            }
+        }
+        self.transitions(|cell| cell != OUTSIDE)
+    }
+}
+
+/// Flood fills from `min` (outside the droplet) to find the exterior, then
+/// splits the remaining air into pockets and the lava into components.
+fn analyse(dots: &HashSet<Dot>, min: &Dot, max: &Dot) -> Report {
+    let outside = flood(min, |dot| fits(dot, min, max) && !dots.contains(dot));
+
     let mut seen: HashSet<Dot> = HashSet::new();
-    let mut queue: VecDeque<Dot> = VecDeque::new();
+    let mut pockets = Vec::new();
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                let dot = Dot::of(x, y, z);
+                if dots.contains(&dot) || outside.contains(&dot) || seen.contains(&dot) {
+                    continue;
+                }
+                let air = flood(&dot, |dot| !dots.contains(dot));
+                let surface = air
+                    .iter()
+                    .map(|dot| {
+                        adj()
+                            .iter()
+                            .filter(|side| dots.contains(&dot.add(side)))
+                            .count()
+                    })
+                    .sum();
+                let lo = self::min(&air);
+                let hi = self::max(&air);
+                seen.extend(air.iter().cloned());
+                pockets.push(Pocket {
+                    volume: air.len(),
+                    surface,
+                    min: lo,
+                    max: hi,
+                });
+            }
+        }
+    }
 
-    seen.insert(min.clone());
-    queue.push_back(min.clone());
-    while !queue.is_empty() {
-        let dot = queue.pop_front().unwrap();
+    let mut seen: HashSet<Dot> = HashSet::new();
+    let mut components = 0;
+    for dot in dots {
+        if seen.contains(dot) {
+            continue;
+        }
+        seen.extend(flood(dot, |dot| dots.contains(dot)));
+        components += 1;
+    }
 
-        for next in adj() {
-            let next = dot.add(&next);
-            if seen.contains(&next) {
-                continue;
+    Report {
+        volume: dots.len(),
+        components,
+        pockets,
+    }
+}
+
+/// Axis-aligned rectangle of the droplet surface, corners counter-clockwise
+/// when seen from outside, `normal` pointing outwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Quad {
+    corners: [[isize; 3]; 4],
+    normal: [isize; 3],
+}
+
+/// Exterior surface as quads: for every axis, direction and slice, faces of
+/// lava cubes that touch outside air are merged greedily into rectangles.
+fn mesh(dots: &HashSet<Dot>, min: &Dot, max: &Dot) -> Vec<Quad> {
+    let outside = flood(min, |dot| fits(dot, min, max) && !dots.contains(dot));
+    let lo = [min.x, min.y, min.z];
+    let hi = [max.x, max.y, max.z];
+    let at = |p: [isize; 3]| Dot::of(p[0], p[1], p[2]);
+
+    let mut quads = Vec::new();
+    for a in 0..3 {
+        let (b, c) = ((a + 1) % 3, (a + 2) % 3);
+        for dir in [-1isize, 1] {
+            for k in lo[a]..=hi[a] {
+                let point = |u: isize, v: isize| {
+                    let mut p = [0; 3];
+                    p[a] = k;
+                    p[b] = u;
+                    p[c] = v;
+                    p
+                };
+                let exposed = |u: isize, v: isize| {
+                    let p = point(u, v);
+                    let mut q = p;
+                    q[a] += dir;
+                    dots.contains(&at(p)) && outside.contains(&at(q))
+                };
+
+                let mut done: HashSet<(isize, isize)> = HashSet::new();
+                for u in lo[b]..=hi[b] {
+                    for v in lo[c]..=hi[c] {
+                        if done.contains(&(u, v)) || !exposed(u, v) {
+                            continue;
+                        }
+                        let free = |u: isize, v: isize| !done.contains(&(u, v)) && exposed(u, v);
+                        let mut v1 = v;
+                        while v1 < hi[c] && free(u, v1 + 1) {
+                            v1 += 1;
+                        }
+                        let mut u1 = u;
+                        while u1 < hi[b] && (v..=v1).all(|v| free(u1 + 1, v)) {
+                            u1 += 1;
+                        }
+                        for u in u..=u1 {
+                            for v in v..=v1 {
+                                done.insert((u, v));
+                            }
+                        }
+
+                        let shift = if dir > 0 { 1 } else { 0 };
+                        let corner = |u: isize, v: isize| {
+                            let mut p = point(u, v);
+                            p[a] += shift;
+                            p
+                        };
+                        let mut corners = [
+                            corner(u, v),
+                            corner(u1 + 1, v),
+                            corner(u1 + 1, v1 + 1),
+                            corner(u, v1 + 1),
+                        ];
+                        if dir < 0 {
+                            corners.reverse();
+                        }
+                        let mut normal = [0; 3];
+                        normal[a] = dir;
+                        quads.push(Quad { corners, normal });
+                    }
+                }
             }
-            if dots.contains(&next) {
-                count += 1;
-                continue;
+        }
+    }
+    quads
+}
+
+/// Wavefront OBJ with shared vertices and one quad face per rectangle.
+fn to_obj(quads: &[Quad]) -> String {
+    let mut index: HashMap<[isize; 3], usize> = HashMap::new();
+    let mut vertices = Vec::new();
+    let mut faces = Vec::new();
+    for quad in quads {
+        let ids = quad
+            .corners
+            .iter()
+            .map(|p| {
+                *index.entry(*p).or_insert_with(|| {
+                    vertices.push(format!("v {} {} {}", p[0], p[1], p[2]));
+                    vertices.len()
+                })
+            })
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+        faces.push(format!("f {}", ids.join(" ")));
+    }
+    let mut out = vec!["o droplet".to_string()];
+    out.extend(vertices);
+    out.extend(faces);
+    out.join("\n") + "\n"
+}
+
+/// ASCII STL, two triangles per rectangle.
+fn to_stl(quads: &[Quad]) -> String {
+    let mut out = vec!["solid droplet".to_string()];
+    for quad in quads {
+        let [a, b, c, d] = quad.corners;
+        for triangle in [[a, b, c], [a, c, d]] {
+            let [x, y, z] = quad.normal;
+            out.push(format!("facet normal {} {} {}", x, y, z));
+            out.push("  outer loop".to_string());
+            for p in triangle {
+                out.push(format!("    vertex {} {} {}", p[0], p[1], p[2]));
             }
-            if fits(&next, min, max) {
+            out.push("  endloop".to_string());
+            out.push("endfacet".to_string());
+        }
+    }
+    out.push("endsolid droplet".to_string());
+    out.join("\n") + "\n"
+}
+
+/// All dots reachable from `start` through faces, stepping only where `open`.
+fn flood<F: Fn(&Dot) -> bool>(start: &Dot, open: F) -> HashSet<Dot> {
+    let mut seen: HashSet<Dot> = HashSet::new();
+    let mut queue: VecDeque<Dot> = VecDeque::new();
+
+    seen.insert(start.clone());
+    queue.push_back(start.clone());
+    while let Some(dot) = queue.pop_front() {
+        for next in adj() {
+            let next = dot.add(&next);
+            if !seen.contains(&next) && open(&next) {
                 queue.push_back(next.clone());
                 seen.insert(next);
             }
         }
     }
 
-    count
+    seen
 }
 
 fn fits(dot: &Dot, min: &Dot, max: &Dot) -> bool {
@@ -117,3 +422,137 @@ impl Dot {
         Dot::of(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
     }
 }
+
+#[cfg(test)]
+mod day18 {
+    use super::*;
+
+    /// Reference: open faces found by looking up the six neighbours of each
+    /// cube.
+    fn surface(dots: &HashSet<Dot>) -> usize {
+        dots.iter()
+            .map(|dot| {
+                adj()
+                    .iter()
+                    .filter(|side| !dots.contains(&dot.add(side)))
+                    .count()
+            })
+            .sum()
+    }
+
+    fn traverse(dots: &HashSet<Dot>, min: &Dot, max: &Dot) -> usize {
+        let mut count = 0;
+        let mut seen: HashSet<Dot> = HashSet::new();
+        let mut queue: VecDeque<Dot> = VecDeque::new();
+
+        seen.insert(min.clone());
+        queue.push_back(min.clone());
+        while !queue.is_empty() {
+            let dot = queue.pop_front().unwrap();
+
+            for next in adj() {
+                let next = dot.add(&next);
+                if seen.contains(&next) {
+                    continue;
+                }
+                if dots.contains(&next) {
+                    count += 1;
+                    continue;
+                }
+                if fits(&next, min, max) {
+                    queue.push_back(next.clone());
+                    seen.insert(next);
+                }
+            }
+        }
+
+        count
+    }
+
+    const EXAMPLE: &str = "2,2,2
+1,2,2
+3,2,2
+2,1,2
+2,3,2
+2,2,1
+2,2,3
+2,2,4
+2,2,6
+1,2,5
+3,2,5
+2,1,5
+2,3,5";
+
+    #[test]
+    fn test_analyse() {
+        let dots = EXAMPLE.lines().map(Dot::parse).collect::<HashSet<_>>();
+        let min = min(&dots).add(&Dot::of(-1, -1, -1));
+        let max = max(&dots).add(&Dot::of(1, 1, 1));
+        assert_eq!(surface(&dots), 64);
+        assert_eq!(traverse(&dots, &min, &max), 58);
+
+        let mut voxels = Voxels::new(&dots, &min, &max);
+        assert_eq!(voxels.surface(), 64);
+        assert_eq!(voxels.exterior(), 58);
+
+        let report = analyse(&dots, &min, &max);
+        assert_eq!(report.volume, 13);
+        assert_eq!(report.components, 6);
+        assert_eq!(
+            report.pockets,
+            vec![Pocket {
+                volume: 1,
+                surface: 6,
+                min: Dot::of(2, 2, 5),
+                max: Dot::of(2, 2, 5),
+            }]
+        );
+    }
+
+    fn area(quad: &Quad) -> usize {
+        let [a, b, _, d] = quad.corners;
+        let side =
+            |p: [isize; 3], q: [isize; 3]| (0..3).map(|i| (p[i] - q[i]).abs()).sum::<isize>();
+        (side(a, b) * side(a, d)) as usize
+    }
+
+    #[test]
+    fn test_mesh() {
+        let dots = EXAMPLE.lines().map(Dot::parse).collect::<HashSet<_>>();
+        let min = min(&dots).add(&Dot::of(-1, -1, -1));
+        let max = max(&dots).add(&Dot::of(1, 1, 1));
+        let quads = mesh(&dots, &min, &max);
+        assert_eq!(quads.iter().map(area).sum::<usize>(), 58);
+
+        let bar = ["1,1,1", "2,1,1", "3,1,1"]
+            .into_iter()
+            .map(Dot::parse)
+            .collect::<HashSet<_>>();
+        let min = Dot::of(0, 0, 0);
+        let max = Dot::of(4, 2, 2);
+        let quads = mesh(&bar, &min, &max);
+        assert_eq!(quads.len(), 6);
+        assert_eq!(quads.iter().map(area).sum::<usize>(), 14);
+        for quad in quads.iter() {
+            let [p, q, _, r] = quad.corners;
+            let u = [q[0] - p[0], q[1] - p[1], q[2] - p[2]];
+            let v = [r[0] - p[0], r[1] - p[1], r[2] - p[2]];
+            let cross = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            let dot = (0..3).map(|i| cross[i] * quad.normal[i]).sum::<isize>();
+            assert!(dot > 0, "{:?}", quad);
+        }
+
+        let obj = to_obj(&quads);
+        assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), 8);
+        assert_eq!(obj.lines().filter(|line| line.starts_with("f ")).count(), 6);
+        let stl = to_stl(&quads);
+        assert_eq!(
+            stl.lines().filter(|line| line.starts_with("facet")).count(),
+            12
+        );
+    }
+}
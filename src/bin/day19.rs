@@ -1,214 +1,475 @@
-use std::collections::{HashSet, VecDeque};
+use std::{
+    env::args,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
 
 use advent_of_code_2022::lines;
 
 fn main() {
-    let costs = lines()
+    let opts = args().skip(1).collect::<Vec<_>>();
+    let explain_plans = opts.iter().any(|opt| opt == "--explain");
+    let show_timing = opts.iter().any(|opt| opt == "--time");
+    let opt = |name: &str| {
+        opts.iter().position(|opt| opt == name).map(|idx| {
+            opts.get(idx + 1)
+                .unwrap_or_else(|| panic!("{} <resource>", name))
+        })
+    };
+    let start = opt("--start").map(String::as_str).unwrap_or("ore");
+    let target = opt("--target").map(String::as_str).unwrap_or("geode");
+    let goal = |bp: &Blueprint| Goal::of(bp, start, target);
+    let blueprints = lines()
         .into_iter()
-        .map(|line| Cost::parse(&line))
+        .map(|line| Blueprint::parse(&line))
         .collect::<Vec<_>>();
 
     let time1 = 24;
-    let part1 = costs
+    let plans = par_map(&blueprints, |bp| timed(|| dfs(bp, &goal(bp), time1)));
+    let part1 = blueprints
         .iter()
-        .enumerate()
-        .map(|(idx, spec)| (idx as i32 + 1) * bfs(spec, time1))
+        .zip(plans.iter())
+        .map(|(bp, (plan, elapsed))| {
+            let quality = bp.id * plan.total;
+            if show_timing {
+                println!(
+                    "Blueprint {}: {} in {} minutes ({} ms)",
+                    bp.id,
+                    amount(plan.total, target),
+                    time1,
+                    elapsed.as_millis()
+                );
+            }
+            if explain_plans {
+                println!(
+                    "Blueprint {}: {}, quality level {}\n",
+                    bp.id,
+                    amount(plan.total, target),
+                    quality
+                );
+                println!("{}", explain(bp, &goal(bp), time1, plan));
+            }
+            quality
+        })
         .sum::<i32>();
     println!("{}", part1);
 
     let time2 = 32;
-    let part2 = costs
+    let plans = par_map(&blueprints[..blueprints.len().min(3)], |bp| {
+        timed(|| dfs(bp, &goal(bp), time2))
+    });
+    let part2 = blueprints
         .iter()
-        .take(3)
-        .map(|spec| bfs(spec, time2))
+        .zip(plans.iter())
+        .map(|(bp, (plan, elapsed))| {
+            if show_timing {
+                println!(
+                    "Blueprint {}: {} in {} minutes ({} ms)",
+                    bp.id,
+                    amount(plan.total, target),
+                    time2,
+                    elapsed.as_millis()
+                );
+            }
+            plan.total
+        })
         .product::<i32>();
     println!("{}", part2);
 }
 
-// Inspired by:
-// https://github.com/wilkotom/AoC2022/blob/main/day19/src/main.rs
-fn bfs(cost: &Cost, time: i32) -> i32 {
-    let robots = Robots {
-        ore: 1,
-        cla: 0,
-        obs: 0,
-        geo: 0,
-    };
-    let spare = Spare::default();
+/// `"1 geode"`, `"9 geodes"`.
+fn amount(n: i32, res: &str) -> String {
+    let s = if n == 1 { "" } else { "s" };
+    format!("{} {}{}", n, res, s)
+}
+
+fn timed<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+    let at = Instant::now();
+    let ret = f();
+    (ret, at.elapsed())
+}
 
-    let mut seen = HashSet::new();
-    let mut queue = VecDeque::new();
-    queue.push_back((time, robots, spare));
+/// Maps items on a pool of scoped threads (one per available core), each
+/// pulling the next unclaimed index, and returns results in input order.
+fn par_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(items.len());
+    let next = AtomicUsize::new(0);
+
+    let mut results = thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        if idx >= items.len() {
+                            break done;
+                        }
+                        done.push((idx, f(&items[idx])));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
 
-    let max_ore = cost.max_ore();
+    results.sort_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, r)| r).collect()
+}
 
-    let mut best = 0;
-    while let Some(state) = queue.pop_front() {
-        let (time, robots, spare) = state;
-        best = best.max(spare.geo);
+/// Resource the first robot collects and resource to maximise.
+struct Goal {
+    start: usize,
+    target: usize,
+}
 
-        if seen.contains(&state) || time == 0 {
-            continue;
+impl Goal {
+    fn of(bp: &Blueprint, start: &str, target: &str) -> Self {
+        let find = |name: &str| {
+            bp.names
+                .iter()
+                .position(|x| x == name)
+                .unwrap_or_else(|| panic!("Blueprint {}: no '{}' resource", bp.id, name))
+        };
+        Self {
+            start: find(start),
+            target: find(target),
         }
+    }
+}
+
+/// Depth-first branch-and-bound: each branch picks the next robot to build and
+/// skips the idle minutes needed to afford it, so the tree only has decision
+/// points where something actually gets built.
+fn dfs(bp: &Blueprint, goal: &Goal, time: i32) -> Plan {
+    let mut robots = vec![0; bp.names.len()];
+    robots[goal.start] = 1;
+
+    let caps = (0..bp.names.len())
+        .map(|res| {
+            if res == goal.target {
+                i32::MAX
+            } else {
+                bp.recipes
+                    .iter()
+                    .map(|recipe| recipe.cost[res])
+                    .max()
+                    .unwrap_or_default()
+            }
+        })
+        .collect();
+
+    // Recipes for the target go first, the rest from the most advanced one.
+    let mut order = (0..bp.recipes.len()).collect::<Vec<_>>();
+    order.sort_by_key(|idx| (bp.recipes[*idx].makes != goal.target, usize::MAX - idx));
+
+    let mut search = Search {
+        bp,
+        target: goal.target,
+        time,
+        caps,
+        order,
+        path: Vec::new(),
+        best: Plan::default(),
+    };
+    search.run(time, robots, vec![0; bp.names.len()]);
+    search.best
+}
+
+/// Winning build order: the minute (1-based) each recipe gets built.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Plan {
+    total: i32,
+    builds: Vec<(i32, usize)>,
+}
+
+struct Search<'a> {
+    bp: &'a Blueprint,
+    target: usize,
+    time: i32,
+    caps: Vec<i32>,
+    order: Vec<usize>,
+    path: Vec<(i32, usize)>,
+    best: Plan,
+}
 
-        if spare.geo < best {
-            continue;
+impl Search<'_> {
+    fn run(&mut self, left: i32, robots: Vec<i32>, spare: Vec<i32>) {
+        let total = spare[self.target] + robots[self.target] * left;
+        if total > self.best.total {
+            self.best = Plan {
+                total,
+                builds: self.path.clone(),
+            };
+        }
+        if self.bound(left, &robots, &spare) <= self.best.total {
+            return;
         }
 
-        seen.insert(state);
+        for idx in self.order.clone() {
+            let recipe = &self.bp.recipes[idx];
+            if robots[recipe.makes] >= self.caps[recipe.makes] {
+                continue;
+            }
+            let wait = match recipe.wait(&robots, &spare) {
+                Some(wait) if wait < left => wait,
+                _ => continue,
+            };
 
-        if spare.ore >= cost.geo.ore && spare.obs >= cost.geo.obs {
-            let mut spare = spare.add(&robots);
-            spare.ore -= cost.geo.ore;
-            spare.obs -= cost.geo.obs;
-            let mut robots = robots;
-            robots.geo += 1;
-            queue.push_back((time - 1, robots, spare));
-            continue;
+            let spare = spare
+                .iter()
+                .zip(robots.iter())
+                .zip(recipe.cost.iter())
+                .map(|((have, rate), cost)| have + rate * wait - cost)
+                .collect();
+            let mut robots = robots.clone();
+            robots[recipe.makes] += 1;
+            self.path.push((self.time - left + wait, idx));
+            self.run(left - wait, robots, spare);
+            self.path.pop();
         }
+    }
 
-        if spare.ore >= cost.ore && robots.ore < max_ore {
-            let mut spare = spare.add(&robots);
-            spare.ore -= cost.ore;
-            let mut robots = robots;
-            robots.ore += 1;
-            queue.push_back((time - 1, robots, spare));
+    /// Optimistic target count: every other robot appears for free each
+    /// minute, and a target robot is built whenever its cost is covered,
+    /// without spending any of the target itself (for targets like ore).
+    fn bound(&self, left: i32, robots: &[i32], spare: &[i32]) -> i32 {
+        let mut robots = robots.to_vec();
+        let mut spare = spare.to_vec();
+        for _ in 0..left {
+            let build = self
+                .bp
+                .recipes
+                .iter()
+                .find(|recipe| recipe.makes == self.target && recipe.affords(&spare));
+            if let Some(recipe) = build {
+                spare
+                    .iter_mut()
+                    .zip(recipe.cost.iter())
+                    .enumerate()
+                    .filter(|(res, _)| *res != self.target)
+                    .for_each(|(_, (have, cost))| *have -= cost);
+            }
+            spare
+                .iter_mut()
+                .zip(robots.iter())
+                .for_each(|(have, rate)| *have += rate);
+            self.bp
+                .recipes
+                .iter()
+                .filter(|recipe| recipe.makes != self.target)
+                .for_each(|recipe| robots[recipe.makes] += 1);
+            if build.is_some() {
+                robots[self.target] += 1;
+            }
         }
+        spare[self.target]
+    }
+}
+
+/// Replays the plan minute by minute in the style of the puzzle walkthrough.
+fn explain(bp: &Blueprint, goal: &Goal, time: i32, plan: &Plan) -> String {
+    let mut robots = vec![0; bp.names.len()];
+    robots[goal.start] = 1;
+    let mut spare = vec![0; bp.names.len()];
+    let mut builds = plan.builds.iter().peekable();
+
+    let mut out = Vec::new();
+    for minute in 1..=time {
+        out.push(format!("== Minute {} ==", minute));
 
-        if spare.ore >= cost.cla && robots.cla < cost.obs.cla {
-            let mut spare = spare.add(&robots);
-            spare.ore -= cost.cla;
-            let mut robots = robots;
-            robots.cla += 1;
-            queue.push_back((time - 1, robots, spare));
+        let build = builds
+            .next_if(|(at, _)| *at == minute)
+            .map(|(_, idx)| &bp.recipes[*idx]);
+        if let Some(recipe) = build {
+            let price = recipe
+                .cost
+                .iter()
+                .enumerate()
+                .filter(|(_, n)| **n > 0)
+                .map(|(res, n)| format!("{} {}", n, bp.names[res]))
+                .collect::<Vec<_>>()
+                .join(" and ");
+            let robot = robot(&bp.names[recipe.makes]);
+            let article = if robot.starts_with(['a', 'e', 'i', 'o', 'u']) {
+                "an"
+            } else {
+                "a"
+            };
+            out.push(format!(
+                "Spend {} to start building {} {} robot.",
+                price, article, robot
+            ));
+            spare
+                .iter_mut()
+                .zip(recipe.cost.iter())
+                .for_each(|(have, cost)| *have -= cost);
         }
 
-        if spare.ore >= cost.obs.ore && spare.cla >= cost.obs.cla {
-            let mut spare = spare.add(&robots);
-            spare.ore -= cost.obs.ore;
-            spare.cla -= cost.obs.cla;
-            let mut robots = robots;
-            robots.obs += 1;
-            queue.push_back((time - 1, robots, spare));
+        for (res, n) in robots.iter().enumerate() {
+            if *n == 0 {
+                continue;
+            }
+            spare[res] += n;
+            let name = &bp.names[res];
+            let s = if *n == 1 { "" } else { "s" };
+            let line = if name == "geode" {
+                let verb = if *n == 1 { "cracks" } else { "crack" };
+                let t = if spare[res] == 1 { "" } else { "s" };
+                format!(
+                    "{} geode-cracking robot{} {} {} geode{}; you now have {} open geode{}.",
+                    n, s, verb, n, s, spare[res], t
+                )
+            } else {
+                let verb = if *n == 1 { "collects" } else { "collect" };
+                format!(
+                    "{} {} robot{} {} {} {}; you now have {} {}.",
+                    n,
+                    robot(name),
+                    s,
+                    verb,
+                    n,
+                    name,
+                    spare[res],
+                    name
+                )
+            };
+            out.push(line);
         }
 
-        queue.push_back((time - 1, robots, spare.add(&robots)));
+        if let Some(recipe) = build {
+            robots[recipe.makes] += 1;
+            out.push(format!(
+                "The new {} robot is ready; you now have {} of them.",
+                robot(&bp.names[recipe.makes]),
+                robots[recipe.makes]
+            ));
+        }
+        out.push(String::new());
     }
-    best
+    out.join("\n")
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-struct Spare {
-    ore: i32,
-    cla: i32,
-    obs: i32,
-    geo: i32,
-}
-
-impl Spare {
-    fn add(self, robots: &Robots) -> Self {
-        let mut this = self;
-        this.ore += robots.ore;
-        this.cla += robots.cla;
-        this.obs += robots.obs;
-        this.geo += robots.geo;
-        this
+fn robot(name: &str) -> String {
+    if name == "geode" {
+        "geode-cracking".to_string()
+    } else {
+        format!("{}-collecting", name)
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-struct Robots {
-    ore: i32,
-    cla: i32,
-    obs: i32,
-    geo: i32,
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Recipe {
+    makes: usize,
+    cost: Vec<i32>,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-struct Cost {
-    ore: i32,
-    cla: i32,
-    obs: ObsCost,
-    geo: GeoCost,
-}
+impl Recipe {
+    fn affords(&self, spare: &[i32]) -> bool {
+        self.cost
+            .iter()
+            .zip(spare.iter())
+            .all(|(cost, have)| have >= cost)
+    }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-struct ObsCost {
-    ore: i32,
-    cla: i32,
+    /// Minutes until this robot is built (including the minute of building
+    /// itself), or `None` if current robots never collect enough.
+    fn wait(&self, robots: &[i32], spare: &[i32]) -> Option<i32> {
+        self.cost
+            .iter()
+            .zip(spare.iter())
+            .zip(robots.iter())
+            .map(|((need, have), rate)| {
+                if have >= need {
+                    Some(0)
+                } else if *rate == 0 {
+                    None
+                } else {
+                    Some((need - have + rate - 1) / rate)
+                }
+            })
+            .try_fold(0, |acc, wait| wait.map(|wait| acc.max(wait)))
+            .map(|wait| wait + 1)
+    }
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-struct GeoCost {
-    ore: i32,
-    obs: i32,
+/// Any number of resources, discovered in order of appearance, and any number
+/// of robot recipes, each costing an arbitrary mix of those resources.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Blueprint {
+    id: i32,
+    names: Vec<String>,
+    recipes: Vec<Recipe>,
 }
 
-impl Cost {
-    fn max_ore(&self) -> i32 {
-        [self.ore, self.cla, self.obs.ore, self.geo.ore]
-            .into_iter()
-            .max()
-            .unwrap_or_default()
-    }
-
+impl Blueprint {
     fn parse(line: &str) -> Self {
-        let line = {
-            let mut it = line.split(':');
-            it.next();
-            it.next().unwrap()
+        let (head, body) = line
+            .split_once(':')
+            .unwrap_or_else(|| panic!("No ':' in '{}'", line));
+        let id = head
+            .split_ascii_whitespace()
+            .last()
+            .and_then(|id| id.parse().ok())
+            .unwrap_or_else(|| panic!("No blueprint id in '{}'", head));
+
+        let mut names: Vec<String> = Vec::new();
+        let mut index = |name: &str| match names.iter().position(|x| x == name) {
+            Some(idx) => idx,
+            None => {
+                names.push(name.to_string());
+                names.len() - 1
+            }
         };
 
-        let mut it = line.split('.').take(4);
-        let line1 = it
-            .next()
-            .unwrap()
-            .strip_prefix(" Each ore robot costs ")
-            .unwrap();
-        let cost1 = num(line1);
-        let line2 = it
-            .next()
-            .unwrap()
-            .strip_prefix(" Each clay robot costs ")
-            .unwrap();
-        let cost2 = num(line2);
-
-        let line3 = it
-            .next()
-            .unwrap()
-            .strip_prefix(" Each obsidian robot costs ")
-            .unwrap();
-        let mut it3 = line3.split(" and ");
-        let cost31 = num(it3.next().unwrap());
-        let cost32 = num(it3.next().unwrap());
-
-        let line4 = it
-            .next()
-            .unwrap()
-            .strip_prefix(" Each geode robot costs ")
-            .unwrap();
-        let mut it4 = line4.split(" and ");
-        let cost41 = num(it4.next().unwrap());
-        let cost42 = num(it4.next().unwrap());
-
-        Cost {
-            ore: cost1,
-            cla: cost2,
-            obs: ObsCost {
-                ore: cost31,
-                cla: cost32,
-            },
-            geo: GeoCost {
-                ore: cost41,
-                obs: cost42,
-            },
-        }
-    }
-}
+        let mut recipes = body
+            .split('.')
+            .map(str::trim)
+            .filter(|sentence| !sentence.is_empty())
+            .map(|sentence| {
+                let words = sentence
+                    .split(|c: char| c.is_ascii_whitespace() || c == ',')
+                    .filter(|word| !word.is_empty())
+                    .collect::<Vec<_>>();
+                let robot = words
+                    .iter()
+                    .position(|word| *word == "robot")
+                    .filter(|pos| *pos > 0)
+                    .unwrap_or_else(|| panic!("No robot in '{}'", sentence));
+                let makes = index(words[robot - 1]);
 
-fn num(line: &str) -> i32 {
-    let mut it = line.split_ascii_whitespace();
-    it.next().unwrap().parse().unwrap()
+                let mut cost = Vec::new();
+                for pair in words[robot + 1..].windows(2) {
+                    if let Ok(n) = pair[0].parse::<i32>() {
+                        let res = index(pair[1]);
+                        if cost.len() <= res {
+                            cost.resize(res + 1, 0);
+                        }
+                        cost[res] += n;
+                    }
+                }
+                Recipe { makes, cost }
+            })
+            .collect::<Vec<_>>();
+
+        recipes
+            .iter_mut()
+            .for_each(|recipe| recipe.cost.resize(names.len(), 0));
+
+        Self { id, names, recipes }
+    }
 }
 
 #[cfg(test)]
@@ -219,13 +480,110 @@ mod day19 {
     fn test_parse() {
         let s = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 4 ore. Each obsidian robot costs 4 ore and 20 clay. Each geode robot costs 2 ore and 12 obsidian.";
         assert_eq!(
-            Cost::parse(s),
-            Cost {
-                ore: 4,
-                cla: 4,
-                obs: ObsCost { ore: 4, cla: 20 },
-                geo: GeoCost { ore: 2, obs: 12 },
+            Blueprint::parse(s),
+            Blueprint {
+                id: 1,
+                names: vec![
+                    "ore".to_string(),
+                    "clay".to_string(),
+                    "obsidian".to_string(),
+                    "geode".to_string()
+                ],
+                recipes: vec![
+                    Recipe {
+                        makes: 0,
+                        cost: vec![4, 0, 0, 0]
+                    },
+                    Recipe {
+                        makes: 1,
+                        cost: vec![4, 0, 0, 0]
+                    },
+                    Recipe {
+                        makes: 2,
+                        cost: vec![4, 20, 0, 0]
+                    },
+                    Recipe {
+                        makes: 3,
+                        cost: vec![2, 0, 12, 0]
+                    },
+                ],
             }
         );
     }
+
+    #[test]
+    fn test_parse_variant() {
+        let s = "Blueprint 7: Each sand robot costs 1 clay, 1 ore. Each ore robot costs 2 ore. Each clay robot costs 2 ore. Each geode robot costs 3 sand and 2 ore.";
+        let bp = Blueprint::parse(s);
+        assert_eq!(bp.id, 7);
+        assert_eq!(bp.names, vec!["sand", "clay", "ore", "geode"]);
+        assert_eq!(bp.recipes[0].cost, vec![0, 1, 1, 0]);
+        assert_eq!(bp.recipes[3].cost, vec![3, 0, 2, 0]);
+        assert!(dfs(&bp, &Goal::of(&bp, "ore", "geode"), 24).total > 0);
+    }
+
+    const EXAMPLE: [&str; 2] = [
+        "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.",
+        "Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.",
+    ];
+
+    fn max_geodes(bp: &Blueprint, time: i32) -> i32 {
+        dfs(bp, &Goal::of(bp, "ore", "geode"), time).total
+    }
+
+    #[test]
+    fn test_example() {
+        let bps = EXAMPLE.map(Blueprint::parse);
+        assert_eq!(max_geodes(&bps[0], 24), 9);
+        assert_eq!(max_geodes(&bps[1], 24), 12);
+        assert_eq!(max_geodes(&bps[0], 32), 56);
+        assert_eq!(max_geodes(&bps[1], 32), 62);
+    }
+
+    #[test]
+    fn test_other_targets() {
+        let bp = Blueprint::parse(EXAMPLE[0]);
+        let best = |start: &str, target: &str, time: i32| {
+            dfs(&bp, &Goal::of(&bp, start, target), time).total
+        };
+        assert_eq!(best("ore", "obsidian", 16), 6);
+        assert_eq!(best("ore", "ore", 12), 15);
+        assert_eq!(best("ore", "clay", 12), 25);
+    }
+
+    #[test]
+    fn test_example_reordered() {
+        let bp = Blueprint::parse("Blueprint 1: Each geode robot costs 7 obsidian and 2 ore. Each obsidian robot costs 14 clay and 3 ore. Each clay robot costs 2 ore. Each ore robot costs 4 ore.");
+        assert_eq!(max_geodes(&bp, 24), 9);
+    }
+
+    #[test]
+    fn test_par_map() {
+        let items = (0..100).collect::<Vec<usize>>();
+        assert_eq!(
+            par_map(&items, |x| x * x),
+            items.iter().map(|x| x * x).collect::<Vec<_>>()
+        );
+        assert!(par_map(&[] as &[usize], |x| *x).is_empty());
+    }
+
+    #[test]
+    fn test_explain() {
+        let bp = Blueprint::parse(EXAMPLE[0]);
+        let goal = Goal::of(&bp, "ore", "geode");
+        let plan = dfs(&bp, &goal, 24);
+        let text = explain(&bp, &goal, 24, &plan);
+        assert!(text.starts_with("== Minute 1 ==\n1 ore-collecting robot collects 1 ore"));
+        assert!(text.contains("== Minute 24 =="));
+        assert!(text.contains("you now have 9 open geodes."));
+        assert_eq!(amount(9, "geode"), "9 geodes");
+        assert_eq!(amount(1, "geode"), "1 geode");
+        assert_eq!(
+            plan.builds
+                .iter()
+                .filter(|(_, idx)| bp.recipes[*idx].makes == goal.target)
+                .count(),
+            2
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use advent_of_code_2022::lines;
+use advent_of_code_2022::{lines, Ring};
 
 fn main() {
     let xs = lines()
@@ -6,49 +6,92 @@ fn main() {
         .map(|line| line.parse::<i64>().unwrap())
         .collect::<Vec<_>>();
 
-    let decoded = mix(&xs, 1, 1);
+    let decoded = mix_ring(&xs, 1, 1);
     let part1 = ans(&decoded);
     println!("{}", part1);
 
     let key: i64 = 811589153;
     let rounds = 10;
-    let decoded = mix(&xs, key, rounds);
+    let decoded = mix_ring(&xs, key, rounds);
     let part2 = ans(&decoded);
     println!("{}", part2);
 }
 
-fn ans(xs: &[i64]) -> i64 {
-    let idx = xs.iter().position(|x| x == &0).unwrap();
+fn ans(ring: &Ring<i64>) -> i64 {
+    let zero = ring.find(|x| x == &0).unwrap();
     [1000, 2000, 3000]
         .into_iter()
-        .map(|offset| (offset + idx) % xs.len())
-        .map(|i| xs[i])
+        .map(|offset| ring.at(zero, offset))
         .sum()
 }
 
-// Inspired by:
-// https://github.com/wilkotom/AoC2022/blob/main/day20/src/main.rs
-fn mix(xs: &[i64], key: i64, rounds: usize) -> Vec<i64> {
-    let mut ret = xs
-        .iter()
-        .copied()
-        .map(|x| x * key)
-        .enumerate()
-        .collect::<Vec<_>>();
-
+/// Mixes `xs` times `key` for `rounds` rounds on a `Ring`, O(√n) per move.
+fn mix_ring(xs: &[i64], key: i64, rounds: usize) -> Ring<i64> {
+    let mut ring = Ring::new(xs.iter().map(|x| x * key).collect());
+    let handles = ring.handles().collect::<Vec<_>>();
     for _ in 0..rounds {
-        for (i, x) in xs.iter().map(|x| *x * key).enumerate() {
-            let idx = ret.iter().position(|item| item == &(i, x)).unwrap();
-            ret.remove(idx);
-
-            let idx = (idx as i64 + x).rem_euclid(ret.len() as i64) as usize;
-            if idx == 0 {
-                ret.push((i, x));
-            } else {
-                ret.insert(idx, (i, x));
+        for handle in handles.iter() {
+            let x = *ring.get(*handle);
+            ring.shift(*handle, x);
+        }
+    }
+    ring
+}
+
+#[cfg(test)]
+mod day20 {
+    use super::*;
+
+    // Inspired by:
+    // https://github.com/wilkotom/AoC2022/blob/main/day20/src/main.rs
+    fn mix(xs: &[i64], key: i64, rounds: usize) -> Vec<i64> {
+        let mut ret = xs
+            .iter()
+            .copied()
+            .map(|x| x * key)
+            .enumerate()
+            .collect::<Vec<_>>();
+
+        for _ in 0..rounds {
+            for (i, x) in xs.iter().map(|x| *x * key).enumerate() {
+                let idx = ret.iter().position(|item| item == &(i, x)).unwrap();
+                ret.remove(idx);
+
+                let idx = (idx as i64 + x).rem_euclid(ret.len() as i64) as usize;
+                if idx == 0 {
+                    ret.push((i, x));
+                } else {
+                    ret.insert(idx, (i, x));
+                }
             }
         }
+
+        ret.into_iter().map(|(_, x)| x).collect()
     }
 
-    ret.into_iter().map(|(_, x)| x).collect()
+    const EXAMPLE: [i64; 7] = [1, 2, -3, 3, -2, 0, 4];
+
+    #[test]
+    fn test_example() {
+        assert_eq!(ans(&mix_ring(&EXAMPLE, 1, 1)), 3);
+        assert_eq!(ans(&mix_ring(&EXAMPLE, 811589153, 10)), 1623178306);
+    }
+
+    #[test]
+    fn test_same_as_mix() {
+        let mut seed: i64 = 42;
+        let xs = (0..500)
+            .map(|_| {
+                seed = (seed * 1103515245 + 12345) % (1 << 31);
+                seed % 2001 - 1000
+            })
+            .collect::<Vec<_>>();
+        for (key, rounds) in [(1, 1), (811589153, 3)] {
+            let ring = mix_ring(&xs, key, rounds);
+            assert_eq!(
+                ring.iter().copied().collect::<Vec<_>>(),
+                mix(&xs, key, rounds)
+            );
+        }
+    }
 }
@@ -1,89 +1,698 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    env::args,
+    fs,
+    iter::once,
+    time::Instant,
+};
 
 use advent_of_code_2022::{lines, Cell, Face, Grid};
 
 fn main() {
-    let mut grid = Grid::parse(&lines(), (0, 0));
+    let grid = Grid::parse(&lines(), (0, 0));
 
+    let opts = args().skip(1).collect::<Vec<_>>();
+    let rules = opts
+        .iter()
+        .position(|opt| opt == "--rules")
+        .and_then(|idx| opts.get(idx + 1))
+        .map(|spec| Rules::parse(spec))
+        .unwrap_or_default();
+
+    if opts.iter().any(|opt| opt == "--bench") {
+        bench(&grid, &rules);
+        return;
+    }
+
+    let path = opts
+        .iter()
+        .position(|opt| opt == "--csv")
+        .map(|idx| opts.get(idx + 1).expect("--csv <path>"));
+    let mut csv = path.map(|_| vec![Stats::HEADER.to_string()]);
+
+    let mut part1 = None;
+    let mut elves = Elves::new(&grid.pins);
+    let limit = opts
+        .iter()
+        .position(|opt| opt == "--rounds")
+        .map(|idx| {
+            opts.get(idx + 1)
+                .and_then(|arg| arg.parse().ok())
+                .expect("--rounds <number>")
+        })
+        .unwrap_or(ROUNDS);
+    let part2 = simulate(&mut elves, &rules, limit, |stats| {
+        if stats.round == 10 {
+            part1 = Some(stats.empty);
+        }
+        if let Some(csv) = csv.as_mut() {
+            csv.push(stats.to_csv());
+        }
+        stats.moved > 0 || part1.is_none()
+    });
+    // Written before reporting, also when the round limit cut the run short.
+    if let (Some(csv), Some(path)) = (csv, path) {
+        fs::write(path, csv.join("\n") + "\n").unwrap();
+    }
+
+    match part1 {
+        Some(part1) => println!("{}", part1),
+        None => println!("Fewer than 10 rounds played"),
+    }
+    match part2 {
+        Some(part2) => println!("{}", part2),
+        None => println!("Elves still moving after {} rounds", limit),
+    }
+}
+
+/// What one round did to the elves.
+#[derive(Debug, Clone, PartialEq)]
+struct Stats {
+    round: usize,
+    moved: usize,
+    min: Cell,
+    max: Cell,
+    empty: usize,
+    density: f64,
+}
+
+impl Stats {
+    const HEADER: &'static str = "round,moved,min_row,min_col,max_row,max_col,empty,density";
+
+    fn of<S: Sim>(sim: &S, round: usize, moved: usize) -> Self {
+        let (min, max) = sim.bound();
+        let area = ((max.row - min.row + 1) * (max.col - min.col + 1)) as usize;
+        let count = sim.count();
+        Self {
+            round,
+            moved,
+            min,
+            max,
+            empty: area - count,
+            density: count as f64 / area as f64,
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{:.4}",
+            self.round,
+            self.moved,
+            self.min.row,
+            self.min.col,
+            self.max.row,
+            self.max.col,
+            self.empty,
+            self.density
+        )
+    }
+}
+
+/// Either elf simulation, so both can be observed the same way.
+trait Sim {
+    fn round(&mut self, rules: &Rules, step: usize) -> usize;
+    fn bound(&self) -> (Cell, Cell);
+    fn count(&self) -> usize;
+}
+
+impl Sim for Grid {
+    fn round(&mut self, rules: &Rules, step: usize) -> usize {
+        round(self, rules, step)
+    }
+
+    fn bound(&self) -> (Cell, Cell) {
+        self.bound_pin()
+    }
+
+    fn count(&self) -> usize {
+        self.pins.len()
+    }
+}
+
+impl Sim for Elves {
+    fn round(&mut self, rules: &Rules, step: usize) -> usize {
+        Elves::round(self, rules, step)
+    }
+
+    fn bound(&self) -> (Cell, Cell) {
+        Elves::bound(self)
+    }
+
+    fn count(&self) -> usize {
+        Elves::count(self)
+    }
+}
+
+/// Default cap on rounds: some rule sets never settle.
+const ROUNDS: usize = 2000;
+
+/// Runs rounds (numbered from 1) while `observe` returns `true`; returns the
+/// number of the last round played, or `None` if `limit` rounds went by.
+fn simulate<S: Sim, F: FnMut(&Stats) -> bool>(
+    sim: &mut S,
+    rules: &Rules,
+    limit: usize,
+    mut observe: F,
+) -> Option<usize> {
+    for step in 0..limit {
+        let moved = sim.round(rules, step);
+        if !observe(&Stats::of(sim, step + 1, moved)) {
+            return Some(step + 1);
+        }
+    }
+    None
+}
+
+fn bench(grid: &Grid, rules: &Rules) {
+    let at = Instant::now();
+    let mut sparse = grid.clone();
     let mut step: usize = 0;
-    for _ in 0..10 {
-        let fs = faces(step);
-        round(&mut grid, &fs);
+    while round(&mut sparse, rules, step) > 0 {
         step += 1;
     }
+    let sparse_ms = at.elapsed().as_millis();
+
+    let at = Instant::now();
+    let mut dense = Elves::new(&grid.pins);
+    let mut steps: usize = 0;
+    while dense.round(rules, steps) > 0 {
+        steps += 1;
+    }
+    let dense_ms = at.elapsed().as_millis();
+
+    assert_eq!(step, steps);
+    assert_eq!(sparse.pins, dense.cells());
+    println!("rounds: {}", step + 1);
+    println!("sparse: {} ms", sparse_ms);
+    println!(" dense: {} ms", dense_ms);
+}
+
+const FACES: [Face; 4] = [Face::North, Face::South, Face::West, Face::East];
+
+/// A candidate move: step by `step` unless any of `blocked_by` (offsets from
+/// the elf) is taken. The destination itself always blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    step: Cell,
+    blocked_by: Vec<Cell>,
+}
+
+/// What happens when several elves propose the same destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Conflict {
+    /// Nobody moves (the puzzle's rule).
+    Cancel,
+    /// The elf whose rule comes first in this round's order moves.
+    Priority,
+}
+
+/// Diffusion rules: an elf with no neighbours at the `stay` offsets does not
+/// move, otherwise it takes the first open rule, starting `rotate * step`
+/// rules into the list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rules {
+    stay: Vec<Cell>,
+    rules: Vec<Rule>,
+    rotate: usize,
+    conflict: Conflict,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        let zero = Cell::default();
+        Self {
+            stay: zero.adj8(),
+            rules: FACES
+                .iter()
+                .map(|face| Rule {
+                    step: face.as_cell(),
+                    blocked_by: zero.next3(face).to_vec(),
+                })
+                .collect(),
+            rotate: 1,
+            conflict: Conflict::Cancel,
+        }
+    }
+}
+
+impl Rules {
+    /// Rules in the order they are tried at the given round.
+    fn order(&self, step: usize) -> Vec<&Rule> {
+        let n = self.rules.len();
+        (0..n)
+            .map(|i| &self.rules[(self.rotate * step + i) % n])
+            .collect()
+    }
 
-    let part1 = {
-        let mut empty: usize = 0;
-        let (min, max) = grid.bound_pin();
-        for row in min.row..=max.row {
-            for col in min.col..=max.col {
-                let cell = Cell::of(row, col);
-                if !grid.pins.contains(&cell) {
-                    empty += 1;
+    /// Parses rules such as `N:NW,N,NE S:SW,S,SE W:NW,W,SW E:NE,E,SE`, each
+    /// optionally preceded by `stay:<dirs>`, `rotate:<n>` and
+    /// `conflict:cancel|priority`. Omitted settings keep their defaults.
+    fn parse(spec: &str) -> Self {
+        let mut this = Self::default();
+        let mut rules = Vec::new();
+        for token in spec.split_ascii_whitespace() {
+            let (key, value) = token
+                .split_once(':')
+                .unwrap_or_else(|| panic!("Expected '<key>:<value>', got '{}'", token));
+            match key {
+                "stay" => this.stay = value.split(',').map(dir).collect(),
+                "rotate" => this.rotate = value.parse().expect("rotate:<number>"),
+                "conflict" => {
+                    this.conflict = match value {
+                        "cancel" => Conflict::Cancel,
+                        "priority" => Conflict::Priority,
+                        _ => panic!("Unknown conflict policy '{}'", value),
+                    }
                 }
+                _ => rules.push(Rule {
+                    step: dir(key),
+                    blocked_by: value.split(',').map(dir).collect(),
+                }),
             }
         }
-        empty
-    };
-    println!("{}", part1);
-
-    let part2 = loop {
-        let fs = faces(step);
-        let n = round(&mut grid, &fs);
-        step += 1;
-        if n == 0 {
-            break step;
+        if !rules.is_empty() {
+            this.rules = rules;
         }
-    };
-    println!("{}", part2);
+        this
+    }
 }
 
-const FACES: [Face; 4] = [Face::North, Face::South, Face::West, Face::East];
-
-fn faces(offset: usize) -> Vec<Face> {
-    let mut ret = Vec::with_capacity(4);
-    for i in 0..FACES.len() {
-        let face = FACES[(offset + i) % FACES.len()];
-        ret.push(face);
+fn dir(name: &str) -> Cell {
+    match name {
+        "N" => Cell::of(-1, 0),
+        "NE" => Cell::of(-1, 1),
+        "E" => Cell::of(0, 1),
+        "SE" => Cell::of(1, 1),
+        "S" => Cell::of(1, 0),
+        "SW" => Cell::of(1, -1),
+        "W" => Cell::of(0, -1),
+        "NW" => Cell::of(-1, -1),
+        _ => panic!("Unknown direction '{}'", name),
     }
-    ret
 }
 
-fn can_move(cell: &Cell, face: &Face, grid: &Grid) -> bool {
-    cell.next3(face).iter().all(|next| grid.is_dot(next))
+fn can_move(cell: &Cell, rule: &Rule, grid: &Grid) -> bool {
+    once(&rule.step)
+        .chain(rule.blocked_by.iter())
+        .all(|off| grid.is_dot(&(*cell + *off)))
 }
 
-fn can_skip(cell: &Cell, grid: &Grid) -> bool {
-    cell.adj8().iter().all(|next| grid.is_dot(next))
+fn can_skip(cell: &Cell, rules: &Rules, grid: &Grid) -> bool {
+    rules.stay.iter().all(|off| grid.is_dot(&(*cell + *off)))
 }
 
-fn round(grid: &mut Grid, faces: &[Face]) -> usize {
-    let mut steps: HashMap<Cell, Vec<Cell>> = HashMap::new();
+/// Reference implementation on `Grid`; returns how many elves moved.
+fn round(grid: &mut Grid, rules: &Rules, step: usize) -> usize {
+    let order = rules.order(step);
+    let mut steps: HashMap<Cell, Vec<(usize, Cell)>> = HashMap::new();
     grid.pins
         .iter()
-        .filter(|cell| !can_skip(cell, grid))
+        .filter(|cell| !can_skip(cell, rules, grid))
         .for_each(|cell| {
-            faces
+            order
                 .iter()
-                .filter(|face| can_move(cell, face, grid))
+                .enumerate()
+                .filter(|(_, rule)| can_move(cell, rule, grid))
                 .take(1)
-                .map(|face| cell.next(face))
-                .for_each(|next| steps.entry(next).or_default().push(*cell))
+                .map(|(idx, rule)| (idx, *cell + rule.step))
+                .for_each(|(idx, next)| steps.entry(next).or_default().push((idx, *cell)))
         });
 
-    let ret = steps.len();
-
-    steps
+    let moves = steps
         .into_iter()
-        .filter(|(_, from)| from.len() == 1)
-        .map(|(next, from)| (next, from[0]))
-        .for_each(|(next, from)| {
-            grid.pins.remove(&from);
-            grid.dots.remove(&next);
-
-            grid.dots.insert(from);
-            grid.pins.insert(next);
+        .filter_map(|(next, from)| match rules.conflict {
+            Conflict::Cancel if from.len() == 1 => Some((next, from[0].1)),
+            Conflict::Cancel => None,
+            Conflict::Priority => from.into_iter().min().map(|(_, cell)| (next, cell)),
+        })
+        .collect::<Vec<_>>();
+
+    for (_, from) in moves.iter() {
+        grid.pins.remove(from);
+        grid.dots.insert(*from);
+    }
+    for (next, _) in moves.iter() {
+        grid.dots.remove(next);
+        grid.pins.insert(*next);
+    }
+
+    moves.len()
+}
+
+/// Elves as a dense bit grid: one bitmask (`words` x 64 bits) per row, where
+/// bit `c` of row `r` is the cell at `origin + (r, c)`. The grid grows so that
+/// there is always an empty border around the elves before a round.
+struct Elves {
+    origin: Cell,
+    words: usize,
+    rows: Vec<Vec<u64>>,
+}
+
+impl Elves {
+    fn new(pins: &HashSet<Cell>) -> Self {
+        let min = pins
+            .iter()
+            .copied()
+            .reduce(|a, b| a.lo(&b))
+            .unwrap_or_default();
+        let max = pins
+            .iter()
+            .copied()
+            .reduce(|a, b| a.hi(&b))
+            .unwrap_or_default();
+        let words = (max.col - min.col + 1) as usize / 64 + 1;
+        let mut this = Self {
+            origin: min,
+            words,
+            rows: vec![vec![0; words]; (max.row - min.row + 1) as usize],
+        };
+        for cell in pins {
+            let row = (cell.row - min.row) as usize;
+            let col = (cell.col - min.col) as usize;
+            this.rows[row][col / 64] |= 1 << (col % 64);
+        }
+        this
+    }
+
+    fn cells(&self) -> HashSet<Cell> {
+        let mut ret = HashSet::new();
+        for (r, row) in self.rows.iter().enumerate() {
+            for (w, word) in row.iter().enumerate() {
+                let mut word = *word;
+                while word != 0 {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    ret.insert(self.origin + Cell::of(r as i32, (w * 64 + bit) as i32));
+                }
+            }
+        }
+        ret
+    }
+
+    fn count(&self) -> usize {
+        self.rows
+            .iter()
+            .flatten()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Smallest rectangle containing all elves.
+    fn bound(&self) -> (Cell, Cell) {
+        let used = (0..self.rows.len())
+            .filter(|r| self.rows[*r].iter().any(|word| *word != 0))
+            .collect::<Vec<_>>();
+        let (top, bottom) = (used[0], used[used.len() - 1]);
+        let all = self.rows.iter().fold(vec![0u64; self.words], |acc, row| {
+            acc.iter().zip(row.iter()).map(|(a, b)| a | b).collect()
         });
+        let left = (0..self.words)
+            .find(|w| all[*w] != 0)
+            .map(|w| w * 64 + all[w].trailing_zeros() as usize)
+            .unwrap();
+        let right = (0..self.words)
+            .rev()
+            .find(|w| all[*w] != 0)
+            .map(|w| w * 64 + 63 - all[w].leading_zeros() as usize)
+            .unwrap();
+        (
+            self.origin + Cell::of(top as i32, left as i32),
+            self.origin + Cell::of(bottom as i32, right as i32),
+        )
+    }
+
+    fn grow(&mut self) {
+        const PAD: usize = 8;
+        if self
+            .rows
+            .first()
+            .map(|row| row.iter().any(|w| *w != 0))
+            .unwrap_or(true)
+        {
+            self.rows.splice(0..0, vec![vec![0; self.words]; PAD]);
+            self.origin.row -= PAD as i32;
+        }
+        if self
+            .rows
+            .last()
+            .map(|row| row.iter().any(|w| *w != 0))
+            .unwrap_or(true)
+        {
+            self.rows.extend(vec![vec![0; self.words]; PAD]);
+        }
+        if self.rows.iter().any(|row| row[0] & 1 != 0) {
+            self.rows.iter_mut().for_each(|row| row.insert(0, 0));
+            self.words += 1;
+            self.origin.col -= 64;
+        }
+        if self.rows.iter().any(|row| row[self.words - 1] >> 63 != 0) {
+            self.rows.iter_mut().for_each(|row| row.push(0));
+            self.words += 1;
+        }
+    }
+
+    /// Bit `c` of the result is the cell at `(r + off.row, c + off.col)`.
+    fn look(&self, r: usize, off: &Cell) -> Vec<u64> {
+        let row = r as isize + off.row as isize;
+        if row < 0 || row as usize >= self.rows.len() {
+            return vec![0; self.words];
+        }
+        shift(&self.rows[row as usize], off.col)
+    }
+
+    /// One round of proposals and moves; returns how many elves moved.
+    fn round(&mut self, rules: &Rules, step: usize) -> usize {
+        self.grow();
+        let n = self.rows.len();
+        let words = self.words;
+        let order = rules.order(step);
+
+        // Proposals per source row, one mask per rule in round order.
+        let mut prop = vec![vec![vec![0u64; words]; order.len()]; n];
+        for (r, prop) in prop.iter_mut().enumerate() {
+            if self.rows[r].iter().all(|w| *w == 0) {
+                continue;
+            }
+            let near = self.near(r);
+            let alone = free(&near, rules.stay.iter(), words);
+            let mut left = self.rows[r]
+                .iter()
+                .zip(alone.iter())
+                .map(|(cur, alone)| cur & !alone)
+                .collect::<Vec<_>>();
+            for (idx, rule) in order.iter().enumerate() {
+                let free = free(&near, once(&rule.step).chain(rule.blocked_by.iter()), words);
+                for i in 0..words {
+                    prop[idx][i] = left[i] & free[i];
+                    left[i] &= !free[i];
+                }
+            }
+        }
+
+        // Same-rule proposals never share a destination, so tracking what
+        // earlier rules claimed is enough to find contested cells.
+        let mut claimed = vec![vec![0u64; words]; n];
+        let mut twice = vec![vec![0u64; words]; n];
+        let mut moves = prop.clone();
+        for (idx, rule) in order.iter().enumerate() {
+            for r in 0..n {
+                let t = match target(r, rule, n) {
+                    Some(t) => t,
+                    None => continue,
+                };
+                if rules.conflict == Conflict::Priority {
+                    let lost = shift(&claimed[t], rule.step.col);
+                    for i in 0..words {
+                        moves[r][idx][i] &= !lost[i];
+                    }
+                }
+                let dest = shift(&prop[r][idx], -rule.step.col);
+                for i in 0..words {
+                    twice[t][i] |= claimed[t][i] & dest[i];
+                    claimed[t][i] |= dest[i];
+                }
+            }
+        }
+        if rules.conflict == Conflict::Cancel {
+            for (idx, rule) in order.iter().enumerate() {
+                for (r, moves) in moves.iter_mut().enumerate() {
+                    if let Some(t) = target(r, rule, n) {
+                        let lost = shift(&twice[t], rule.step.col);
+                        moves[idx]
+                            .iter_mut()
+                            .zip(lost.iter())
+                            .for_each(|(m, lost)| *m &= !lost);
+                    }
+                }
+            }
+        }
 
+        let mut moved = 0;
+        let mut next = self.rows.clone();
+        for (idx, rule) in order.iter().enumerate() {
+            for r in 0..n {
+                if let Some(t) = target(r, rule, n) {
+                    let dest = shift(&moves[r][idx], -rule.step.col);
+                    for i in 0..words {
+                        next[r][i] &= !moves[r][idx][i];
+                        moved += moves[r][idx][i].count_ones() as usize;
+                    }
+                    for i in 0..words {
+                        next[t][i] |= dest[i];
+                    }
+                }
+            }
+        }
+        self.rows = next;
+        moved
+    }
+
+    /// Cells around row `r`: `near[dr + 1][dc + 1]` bit `c` is the cell at
+    /// `(r + dr, c + dc)`.
+    fn near(&self, r: usize) -> Vec<Vec<Vec<u64>>> {
+        (-1..=1)
+            .map(|dr| (-1..=1).map(|dc| self.look(r, &Cell::of(dr, dc))).collect())
+            .collect()
+    }
+}
+
+/// Bits where none of the cells at the given offsets are taken.
+fn free<'a>(
+    near: &[Vec<Vec<u64>>],
+    offsets: impl Iterator<Item = &'a Cell>,
+    words: usize,
+) -> Vec<u64> {
+    let mut ret = vec![!0u64; words];
+    for off in offsets {
+        let near = &near[(off.row + 1) as usize][(off.col + 1) as usize];
+        ret.iter_mut().zip(near.iter()).for_each(|(a, x)| *a &= !x);
+    }
     ret
 }
+
+/// Row an elf in row `r` moves to under the given rule, if inside the grid.
+fn target(r: usize, rule: &Rule, n: usize) -> Option<usize> {
+    let t = r as isize + rule.step.row as isize;
+    (t >= 0 && (t as usize) < n).then_some(t as usize)
+}
+
+/// Bit `c` of the result is bit `c + dc` of `row`, for `dc` in `-1..=1`.
+fn shift(row: &[u64], dc: i32) -> Vec<u64> {
+    match dc {
+        -1 => (0..row.len())
+            .map(|i| row[i] << 1 | if i > 0 { row[i - 1] >> 63 } else { 0 })
+            .collect(),
+        0 => row.to_vec(),
+        1 => (0..row.len())
+            .map(|i| {
+                row[i] >> 1
+                    | if i + 1 < row.len() {
+                        row[i + 1] << 63
+                    } else {
+                        0
+                    }
+            })
+            .collect(),
+        _ => panic!("Offset out of range: {}", dc),
+    }
+}
+
+#[cfg(test)]
+mod day23 {
+    use super::*;
+
+    const EXAMPLE: &str = "....#..
+..###.#
+#...#.#
+.#...##
+#.###..
+##.#.##
+.#..#..";
+
+    #[test]
+    fn test_example() {
+        let lines = EXAMPLE.lines().map(|s| s.to_string()).collect::<Vec<_>>();
+        let mut grid = Grid::parse(&lines, (0, 0));
+        let mut elves = Elves::new(&grid.pins);
+        let rules = Rules::default();
+
+        for step in 0..10 {
+            round(&mut grid, &rules, step);
+            elves.round(&rules, step);
+            assert_eq!(grid.pins, elves.cells());
+        }
+        assert_eq!(Stats::of(&elves, 10, 0).empty, 110);
+
+        let mut step = 10;
+        while elves.round(&rules, step) > 0 {
+            step += 1;
+        }
+        assert_eq!(step + 1, 20);
+    }
+
+    #[test]
+    fn test_stats() {
+        let lines = EXAMPLE.lines().map(|s| s.to_string()).collect::<Vec<_>>();
+        let rules = Rules::default();
+
+        let mut sparse = Vec::new();
+        let mut grid = Grid::parse(&lines, (0, 0));
+        let last = simulate(&mut grid, &rules, ROUNDS, |stats| {
+            sparse.push(stats.clone());
+            stats.moved > 0
+        });
+        assert_eq!(last, Some(20));
+
+        let mut dense = Vec::new();
+        let mut elves = Elves::new(&Grid::parse(&lines, (0, 0)).pins);
+        simulate(&mut elves, &rules, ROUNDS, |stats| {
+            dense.push(stats.clone());
+            stats.round < 20
+        });
+
+        assert_eq!(sparse, dense);
+        assert_eq!(dense[9].empty, 110);
+        assert_eq!(dense[9].to_csv(), "10,9,-2,-2,8,9,110,0.1667");
+
+        // Without rotation the example never settles.
+        let mut elves = Elves::new(&Grid::parse(&lines, (0, 0)).pins);
+        let rules = Rules::parse("rotate:0");
+        let mut rounds = 0;
+        let last = simulate(&mut elves, &rules, 50, |stats| {
+            rounds = stats.round;
+            stats.moved > 0
+        });
+        assert_eq!((last, rounds), (None, 50));
+    }
+
+    #[test]
+    fn test_rules_parse() {
+        let spec = "stay:NW,N,NE,W,E,SW,S,SE rotate:1 conflict:cancel N:NE,N,NW S:SE,S,SW W:NW,W,SW E:NE,E,SE";
+        assert_eq!(Rules::parse(spec), Rules::default());
+        assert_eq!(Rules::parse(""), Rules::default());
+    }
+
+    #[test]
+    fn test_rules_variants() {
+        let lines = EXAMPLE.lines().map(|s| s.to_string()).collect::<Vec<_>>();
+        for spec in [
+            "rotate:0",
+            "conflict:priority",
+            "rotate:3 N:NW,NE S:SW,SE NE:N,E SW:S,W W:NW,SW E:NE,SE",
+            "stay:N,S,E,W conflict:priority NW:N,W SE:S,E NE:N,E SW:S,W",
+        ] {
+            let rules = Rules::parse(spec);
+            let mut grid = Grid::parse(&lines, (0, 0));
+            let mut elves = Elves::new(&grid.pins);
+            for step in 0..50 {
+                assert_eq!(
+                    round(&mut grid, &rules, step),
+                    elves.round(&rules, step),
+                    "{}",
+                    spec
+                );
+                assert_eq!(grid.pins, elves.cells(), "{}", spec);
+            }
+        }
+    }
+}
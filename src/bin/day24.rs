@@ -1,7 +1,4 @@
-use std::{
-    collections::{HashSet, VecDeque},
-    time::Instant,
-};
+use std::{collections::HashMap, env::args, iter::once, time::Instant};
 
 use advent_of_code_2022::{lines, Cell, Grid};
 
@@ -11,6 +8,79 @@ fn main() {
 
     let (min, max) = grid.bound_all();
 
+    let (at, goal) = ends(&grid, &min, &max);
+
+    let valley = Valley::new(&grid, &blizzards, &min, &max);
+    let trip = bfs(&valley, 0, &[at, goal, at, goal]);
+
+    let part1 = trip[0];
+    println!(
+        "{} ({}) [{} ms]",
+        part1,
+        part1 == 286,
+        t.elapsed().as_millis()
+    );
+
+    let part2 = trip[2];
+    println!("{} ({})", part2, part2 == 820);
+
+    if args().nth(1).map(|arg| arg == "--dump").unwrap_or_default() {
+        let path = route(&valley, 0, &[at, goal, at, goal]);
+        println!("Initial state:");
+        println!(
+            "{}\n",
+            grid.dump_with_extra(as_extra(&min, &max, &blizzards, 0, &at))
+        );
+        for (time, pair) in path.windows(2).enumerate() {
+            let time = time + 1;
+            let (prev, cell) = (pair[0], pair[1]);
+            let extra = as_extra(&min, &max, &blizzards, time, &cell);
+            println!("Minute {}, {}:", time, describe(&prev, &cell));
+            println!("{}\n", grid.dump_with_extra(extra));
+        }
+    }
+}
+
+fn describe(prev: &Cell, next: &Cell) -> &'static str {
+    match (next.row - prev.row, next.col - prev.col) {
+        (-1, 0) => "move up",
+        (1, 0) => "move down",
+        (0, -1) => "move left",
+        (0, 1) => "move right",
+        _ => "wait",
+    }
+}
+
+/// Valley contents at `time` for `Grid::dump_with_extra`: blizzards as
+/// `<>^v`, or their count where several overlap, and the expedition as `E`.
+fn as_extra(
+    min: &Cell,
+    max: &Cell,
+    blizzards: &[(Cell, char)],
+    time: usize,
+    expedition: &Cell,
+) -> HashMap<Cell, char> {
+    let mut seen: HashMap<Cell, (char, u32)> = HashMap::new();
+    blizzards_at(min, max, blizzards, time).for_each(|(cell, c)| {
+        let e = seen.entry(cell).or_insert((c, 0));
+        e.1 += 1;
+    });
+
+    let mut extra: HashMap<Cell, char> = blizzards.iter().map(|(cell, _)| (*cell, '.')).collect();
+    for (cell, (c, n)) in seen {
+        let c = if n > 1 {
+            char::from_digit(n, 10).unwrap_or('*')
+        } else {
+            c
+        };
+        extra.insert(cell, c);
+    }
+    extra.insert(*expedition, 'E');
+    extra
+}
+
+/// Entrance in the top wall and exit in the bottom wall.
+fn ends(grid: &Grid, min: &Cell, max: &Cell) -> (Cell, Cell) {
     let at = grid
         .dots
         .iter()
@@ -21,83 +91,322 @@ fn main() {
         .iter()
         .find(|c| c.row == max.row && grid.is_dot(c))
         .unwrap();
+    (*at, *goal)
+}
 
-    let part1 = bfs(&grid, &blizzards, 0, at, goal, &min, &max);
-    println!(
-        "{} ({}) [{} ms]",
-        part1,
-        part1 == 286,
-        t.elapsed().as_millis()
-    );
-
-    // let one = bfs(&grid, &blizzards, part1, goal, at, &min, &max);
-    // let two = bfs(&grid, &blizzards, one, at, goal, &min, &max);
-    // println!("{} ({})", two, two == 820);
+/// Earliest trip visiting `stops` in order, starting at `stops[0]` at minute
+/// `time`: returns the arrival minute at each of the following stops (empty if
+/// the trip is impossible). Legs are searched jointly, so waiting at a stop
+/// is never assumed to be safe; among the fastest trips the one with the
+/// earliest intermediate arrivals is reported.
+///
+/// Each minute is a layer of reachable cells (one bitset per leg). A state is
+/// dropped if it was already reached at the same minute modulo the blizzard
+/// period, which also bounds the search when the trip is impossible.
+fn bfs(valley: &Valley, time: usize, stops: &[Cell]) -> Vec<usize> {
+    let layers = plan(valley, time, stops);
+    (1..stops.len())
+        .filter_map(|k| {
+            layers
+                .iter()
+                .position(|layer| layer[k..].iter().any(|bits| !bits.is_empty()))
+                .map(|i| time + i)
+        })
+        .collect()
+}
 
-    // Try making it work fast now (<100ms)?
-    // https://www.reddit.com/r/adventofcode/comments/zu28ij/2022_day_24_solutions/
-    /*
+/// Cell of the expedition at every minute of the trip found by `bfs`, starting
+/// at minute `time` (empty if the trip is impossible).
+fn route(valley: &Valley, time: usize, stops: &[Cell]) -> Vec<Cell> {
+    let layers = plan(valley, time, stops);
+    let legs = stops.len().saturating_sub(1);
+    let last = match layers.last() {
+        Some(layer) => layer,
+        None => return Vec::new(),
+    };
+    debug_assert!(last[legs].get(&stops[legs]));
 
-    time cargo run --release --bin day24 < txt/day24.txt
-    cargo flamegraph --bin day24 < txt/day24.txt
-    python -m http.server
-    http://192.168.1.103:8000/flamegraph.svg
+    let mut path = vec![(stops[legs], legs)];
+    for layer in layers.iter().rev().skip(1) {
+        let (cell, leg) = *path.last().unwrap();
+        let prev = cell
+            .adj4()
+            .into_iter()
+            .chain(once(cell))
+            .flat_map(|prev| (0..=leg).map(move |l| (prev, l)))
+            .find(|(prev, l)| layer[*l].get(prev) && bump(stops, &cell, *l) == leg)
+            .unwrap();
+        path.push(prev);
+    }
+    path.into_iter().rev().map(|(cell, _)| cell).collect()
+}
 
-    */
+/// Leg a traveller on `leg` ends up on after stepping onto `cell`.
+fn bump(stops: &[Cell], cell: &Cell, mut leg: usize) -> usize {
+    while leg + 1 < stops.len() && cell == &stops[leg + 1] {
+        leg += 1;
+    }
+    leg
 }
 
-fn bfs(
-    grid: &Grid,
-    blizzards: &[(Cell, char)],
-    time: usize,
-    from: &Cell,
-    goal: &Cell,
-    min: &Cell,
-    max: &Cell,
-) -> usize {
-    let mut queue: VecDeque<(usize, Cell)> = VecDeque::new();
-    queue.push_back((time, *from));
+/// Reachable cells per leg for every minute of the trip, narrowed down to the
+/// states of a single fastest trip with the earliest intermediate arrivals.
+fn plan(valley: &Valley, time: usize, stops: &[Cell]) -> Vec<Vec<Bits>> {
+    let legs = stops.len().saturating_sub(1);
+    let bump = |cell: &Cell, leg: usize| bump(stops, cell, leg);
+    let empty = Bits::new(valley.rows, valley.cols);
 
-    let mut seen: HashSet<(usize, Cell)> = HashSet::new();
-    seen.insert((time, *from));
+    let mut start = vec![empty.clone(); legs + 1];
+    let leg = bump(&stops[0], 0);
+    start[leg].set(&stops[0]);
+    if leg == legs {
+        return vec![start];
+    }
 
-    while let Some((time, cell)) = queue.pop_front() {
-        // println!("time: {} (queue: {})", time, queue.len());
-        // println!("{}\n", grid.dump_with_extra(as_extra(min, max, blizzards, time, &cell)));
+    let mut seen = vec![vec![empty.clone(); legs + 1]; valley.period];
+    seen[time % valley.period] = start.clone();
 
-        if goal == &cell {
-            return time;
+    // Forward: layers[i] holds states first reached at minute `time + i`.
+    let mut layers = vec![start];
+    loop {
+        let now = time + layers.len();
+        let open = valley.open(now);
+        let mut next = step(layers.last().unwrap(), &stops[1..], &bump, open);
+        let seen = &mut seen[now % valley.period];
+        for (bits, seen) in next.iter_mut().zip(seen.iter_mut()) {
+            bits.and_not(seen);
+            seen.or(bits);
         }
 
-        let time = time + 1;
-
-        let vec = rounds_vec(min, max, blizzards, time);
+        let done = !next[legs].is_empty();
+        let dead = next.iter().all(|bits| bits.is_empty());
+        layers.push(next);
+        if done {
+            break;
+        }
+        if dead {
+            return Vec::new();
+        }
+    }
 
-        for next in cell.adj4() {
-            if seen.contains(&(time, next)) {
-                continue;
-            }
-            if !next.fits(min, max) {
-                continue;
-            }
-            if hits(&next, &vec) {
-                continue;
-            }
-            if grid.pins.contains(&next) {
-                continue;
+    // Backward: keep only states that still reach the last stop in time.
+    let mut good = layers.clone();
+    let last = good.len() - 1;
+    good[last][..legs]
+        .iter_mut()
+        .for_each(|bits| *bits = empty.clone());
+    for i in (0..last).rev() {
+        for leg in 0..=legs {
+            let mut target = good[i + 1][leg].clone();
+            if leg < legs {
+                let stop = &stops[leg + 1];
+                if good[i + 1][bump(stop, leg)].get(stop) {
+                    target.set(stop);
+                }
             }
+            let mut bits = target.expand();
+            bits.and(&layers[i][leg]);
+            good[i][leg] = bits;
+        }
+    }
 
-            queue.push_back((time, next));
-            seen.insert((time, next));
+    // Forward again through good states only, committing to each arrival
+    // as early as possible.
+    let mut leg = leg;
+    let mut cur = good[0].clone();
+    let mut ret = vec![cur.clone()];
+    for (i, good) in good.iter().enumerate().skip(1) {
+        let open = valley.open(time + i);
+        cur = step(&cur, &stops[1..], &bump, open);
+        cur.iter_mut()
+            .zip(good.iter())
+            .for_each(|(bits, good)| bits.and(good));
+        while leg < legs && cur[leg + 1..].iter().any(|bits| !bits.is_empty()) {
+            leg += 1;
+            cur[..leg].iter_mut().for_each(|bits| *bits = empty.clone());
         }
+        ret.push(cur.clone());
+    }
+    ret
+}
 
-        if !hits(&cell, &vec) {
-            queue.push_back((time, cell));
-            seen.insert((time, cell));
+/// One minute of movement for every leg: expand into `open` cells, and move
+/// cells that hit the next stop of their leg on to the following leg(s).
+fn step<B>(layer: &[Bits], next_stops: &[Cell], bump: &B, open: &Bits) -> Vec<Bits>
+where
+    B: Fn(&Cell, usize) -> usize,
+{
+    let legs = layer.len() - 1;
+    let mut next = layer.to_vec();
+    next.iter_mut().for_each(Bits::clear);
+    for leg in 0..legs {
+        let mut reach = layer[leg].expand();
+        reach.and(open);
+        let stop = &next_stops[leg];
+        if reach.get(stop) {
+            reach.unset(stop);
+            next[bump(stop, leg)].set(stop);
         }
+        next[leg].or(&reach);
     }
+    next
+}
 
-    0
+/// Blizzard-free cells for every minute of the `lcm(rows, cols)` period, one
+/// row bitset per grid row (walls are never open, entrance and exit always).
+struct Valley {
+    rows: usize,
+    cols: usize,
+    period: usize,
+    open: Vec<Bits>,
+}
+
+impl Valley {
+    fn new(grid: &Grid, blizzards: &[(Cell, char)], min: &Cell, max: &Cell) -> Self {
+        let rows = (max.row - min.row + 1) as usize;
+        let cols = (max.col - min.col + 1) as usize;
+        let period = lcm(rows - 2, cols - 2);
+
+        let mut base = Bits::new(rows, cols);
+        grid.dots
+            .iter()
+            .chain(blizzards.iter().map(|(cell, _)| cell))
+            .filter(|cell| !grid.pins.contains(cell))
+            .for_each(|cell| base.set(cell));
+
+        let open = (0..period)
+            .map(|time| {
+                let mut bits = base.clone();
+                blizzards_at(min, max, blizzards, time).for_each(|(cell, _)| bits.unset(&cell));
+                bits
+            })
+            .collect();
+
+        Self {
+            rows,
+            cols,
+            period,
+            open,
+        }
+    }
+
+    fn open(&self, time: usize) -> &Bits {
+        &self.open[time % self.period]
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
+
+/// Grid of bits stored row by row, `words` 64-bit words per row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Bits {
+    words: usize,
+    data: Vec<u64>,
+}
+
+impl Bits {
+    fn new(rows: usize, cols: usize) -> Self {
+        let words = cols.div_ceil(64);
+        Self {
+            words,
+            data: vec![0; rows * words],
+        }
+    }
+
+    fn index(&self, cell: &Cell) -> (usize, u64) {
+        let (row, col) = (cell.row as usize, cell.col as usize);
+        (row * self.words + col / 64, 1 << (col % 64))
+    }
+
+    fn get(&self, cell: &Cell) -> bool {
+        let (idx, bit) = self.index(cell);
+        self.data
+            .get(idx)
+            .map(|word| word & bit != 0)
+            .unwrap_or_default()
+    }
+
+    fn set(&mut self, cell: &Cell) {
+        let (idx, bit) = self.index(cell);
+        self.data[idx] |= bit;
+    }
+
+    fn unset(&mut self, cell: &Cell) {
+        let (idx, bit) = self.index(cell);
+        self.data[idx] &= !bit;
+    }
+
+    fn clear(&mut self) {
+        self.data.iter_mut().for_each(|word| *word = 0);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.iter().all(|word| *word == 0)
+    }
+
+    fn and(&mut self, that: &Bits) {
+        self.data
+            .iter_mut()
+            .zip(that.data.iter())
+            .for_each(|(a, b)| *a &= b);
+    }
+
+    fn and_not(&mut self, that: &Bits) {
+        self.data
+            .iter_mut()
+            .zip(that.data.iter())
+            .for_each(|(a, b)| *a &= !b);
+    }
+
+    fn or(&mut self, that: &Bits) {
+        self.data
+            .iter_mut()
+            .zip(that.data.iter())
+            .for_each(|(a, b)| *a |= b);
+    }
+
+    /// Every cell plus its four neighbours (bits may leak past the last
+    /// column, callers mask them out).
+    fn expand(&self) -> Bits {
+        let w = self.words;
+        let rows = self.data.len() / w;
+        let mut ret = self.clone();
+        for row in 0..rows {
+            let at = row * w;
+            for i in 0..w {
+                let word = self.data[at + i];
+                let carry_lo = if i > 0 {
+                    self.data[at + i - 1] >> 63
+                } else {
+                    0
+                };
+                let carry_hi = if i + 1 < w {
+                    self.data[at + i + 1] << 63
+                } else {
+                    0
+                };
+                let mut out = word | word << 1 | carry_lo | word >> 1 | carry_hi;
+                if row > 0 {
+                    out |= self.data[at + i - w];
+                }
+                if row + 1 < rows {
+                    out |= self.data[at + i + w];
+                }
+                ret.data[at + i] = out;
+            }
+        }
+        ret
+    }
 }
 
 fn wrap(offset: i32, period: i32, span: i32) -> i32 {
@@ -108,40 +417,80 @@ fn wrap(offset: i32, period: i32, span: i32) -> i32 {
     }
 }
 
-// hot spot: .for_each takes ~90% of time
-fn rounds_vec(min: &Cell, max: &Cell, blizzards: &[(Cell, char)], time: usize) -> Vec<Vec<u8>> {
+fn blizzards_at<'a>(
+    min: &Cell,
+    max: &Cell,
+    blizzards: &'a [(Cell, char)],
+    time: usize,
+) -> impl Iterator<Item = (Cell, char)> + 'a {
     let time = time as i32;
     let rows = max.row - min.row + 1 - 2;
     let cols = max.col - min.col + 1 - 2;
 
-    let mut ret = vec![vec![0_u8; cols as usize]; rows as usize];
-
-    blizzards
-        .iter()
-        .map(|(cell, c)| match c {
+    blizzards.iter().map(move |(cell, c)| {
+        let cell = match c {
             '^' => Cell::of(wrap(-cell.row, rows, time), cell.col),
             'v' => Cell::of(wrap(cell.row, rows, time), cell.col),
             '<' => Cell::of(cell.row, wrap(-cell.col, cols, time)),
             '>' => Cell::of(cell.row, wrap(cell.col, cols, time)),
             _ => panic!("fuck off already!"),
-        })
-        .for_each(|cell| {
-            let row = cell.row as usize - 1;
-            let col = cell.col as usize - 1;
-            ret[row][col] = 1;
-        });
-
-    ret
+        };
+        (cell, *c)
+    })
 }
 
-fn hits(cell: &Cell, vec: &[Vec<u8>]) -> bool {
-    if cell.row == 0 || cell.col == 0 {
-        return false;
+#[cfg(test)]
+mod day24 {
+    use super::*;
+
+    const EXAMPLE: &str = "#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#";
+
+    #[test]
+    fn test_example() {
+        let lines = EXAMPLE.lines().map(|s| s.to_string()).collect::<Vec<_>>();
+        let (grid, blizzards) = Grid::parse_with_extra(&lines, (0, 0));
+        let (min, max) = grid.bound_all();
+        let (at, goal) = ends(&grid, &min, &max);
+
+        let valley = Valley::new(&grid, &blizzards, &min, &max);
+
+        assert_eq!(valley.period, 12);
+        assert_eq!(bfs(&valley, 0, &[at, goal]), vec![18]);
+        assert_eq!(bfs(&valley, 0, &[at, goal, at, goal]), vec![18, 41, 54]);
+        assert_eq!(bfs(&valley, 18, &[goal, at]), vec![41]);
+        assert_eq!(bfs(&valley, 0, &[at, at]), vec![0]);
+        assert!(bfs(&valley, 0, &[at, Cell::of(0, 0)]).is_empty());
+
+        let path = route(&valley, 0, &[at, goal, at, goal]);
+        assert_eq!(path.len(), 55);
+        assert_eq!(path[0], at);
+        assert_eq!(path[18], goal);
+        assert_eq!(path[41], at);
+        assert_eq!(path[54], goal);
+        assert!(path.windows(2).all(|pair| {
+            let d = (pair[1].row - pair[0].row).abs() + (pair[1].col - pair[0].col).abs();
+            d <= 1
+        }));
     }
-    let row = cell.row as usize - 1;
-    let col = cell.col as usize - 1;
-    if row > vec.len() - 1 || col > vec[0].len() - 1 {
-        return false;
+
+    #[test]
+    fn test_as_extra() {
+        let lines = EXAMPLE.lines().map(|s| s.to_string()).collect::<Vec<_>>();
+        let (grid, blizzards) = Grid::parse_with_extra(&lines, (0, 0));
+        let (min, max) = grid.bound_all();
+
+        let extra = as_extra(&min, &max, &blizzards, 0, &Cell::of(0, 1));
+        assert_eq!(grid.dump_with_extra(extra), EXAMPLE.replacen('.', "E", 1));
+
+        let extra = as_extra(&min, &max, &blizzards, 1, &Cell::of(1, 1));
+        assert_eq!(
+            grid.dump_with_extra(extra),
+            ["#.######", "#E>3.<.#", "#<..<<.#", "#>2.22.#", "#>v..^<#", "######.#"].join("\n")
+        );
     }
-    vec[row][col] > 0
 }
@@ -48,7 +48,6 @@ fn into_snafu(mut n: i64) -> String {
 
 fn from_snafu(n: &str) -> i64 {
     n.chars()
-        .into_iter()
         .rev()
         .enumerate()
         .map(|(i, c)| num(c) * pow5(i as u8))
@@ -154,10 +154,8 @@ impl Grid {
         let (min, max) = self.bound_all();
 
         (min.row..=max.row)
-            .into_iter()
             .map(|row| {
                 (min.col..=max.col)
-                    .into_iter()
                     .map(|col| {
                         let cell = Cell::of(row, col);
                         if extra.contains_key(&cell) {
@@ -201,3 +199,215 @@ fn extra<'a, F: Fn(char) -> bool>(
             })
     })
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Handle(usize);
+
+/// Circular sequence with stable handles: elements keep their handle while
+/// being moved around, so equal values stay distinguishable. Positions are
+/// kept in blocks of about √n handles, so moves and lookups take O(√n).
+#[derive(Debug, Clone)]
+pub struct Ring<T> {
+    items: Vec<T>,
+    blocks: Blocks,
+}
+
+impl<T> Ring<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        let blocks = Blocks::new(items.len());
+        Self { items, blocks }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Handles in the order the elements were given, not in ring order.
+    pub fn handles(&self) -> impl Iterator<Item = Handle> {
+        (0..self.items.len()).map(Handle)
+    }
+
+    pub fn get(&self, handle: Handle) -> &T {
+        &self.items[handle.0]
+    }
+
+    pub fn find<F: Fn(&T) -> bool>(&self, f: F) -> Option<Handle> {
+        self.items.iter().position(f).map(Handle)
+    }
+
+    /// Index of the element counting from the ring's internal start.
+    pub fn position(&self, handle: Handle) -> usize {
+        self.blocks.position(handle.0)
+    }
+
+    /// Element `offset` places after `handle` (before, if negative).
+    pub fn at(&self, handle: Handle, offset: i64) -> &T {
+        let pos = self.position(handle) as i64 + offset;
+        let pos = pos.rem_euclid(self.len() as i64) as usize;
+        &self.items[self.blocks.nth(pos)]
+    }
+
+    /// Moves the element `offset` places forward (backward, if negative)
+    /// among the other elements, wrapping around the ring.
+    pub fn shift(&mut self, handle: Handle, offset: i64) {
+        if self.len() < 2 {
+            return;
+        }
+        let len = self.len() as i64 - 1;
+        let pos = self.blocks.remove(handle.0);
+        let pos = (pos as i64 + offset).rem_euclid(len) as usize;
+        if pos == 0 {
+            self.blocks.insert(len as usize, handle.0);
+        } else {
+            self.blocks.insert(pos, handle.0);
+        }
+    }
+
+    /// All elements in ring order, from the internal start.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.blocks.ids().map(|id| &self.items[id])
+    }
+
+    /// All elements in ring order, starting at `handle`.
+    pub fn iter_from(&self, handle: Handle) -> impl Iterator<Item = &T> + '_ {
+        let pos = self.position(handle);
+        self.iter().skip(pos).chain(self.iter().take(pos))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Blocks {
+    size: usize,
+    blocks: Vec<Vec<usize>>,
+    block_of: Vec<usize>,
+}
+
+impl Blocks {
+    fn new(n: usize) -> Self {
+        let size = ((n as f64).sqrt() as usize).max(1);
+        let mut this = Self {
+            size,
+            blocks: Vec::new(),
+            block_of: vec![0; n],
+        };
+        this.split((0..n).collect());
+        this
+    }
+
+    fn split(&mut self, ids: Vec<usize>) {
+        self.blocks = ids.chunks(self.size).map(|c| c.to_vec()).collect();
+        if self.blocks.is_empty() {
+            self.blocks.push(Vec::new());
+        }
+        for (b, block) in self.blocks.iter().enumerate() {
+            block.iter().for_each(|id| self.block_of[*id] = b);
+        }
+    }
+
+    fn ids(&self) -> impl Iterator<Item = usize> + '_ {
+        self.blocks.iter().flatten().copied()
+    }
+
+    fn position(&self, id: usize) -> usize {
+        let b = self.block_of[id];
+        let before = self.blocks[..b]
+            .iter()
+            .map(|block| block.len())
+            .sum::<usize>();
+        before + self.blocks[b].iter().position(|x| *x == id).unwrap()
+    }
+
+    fn nth(&self, mut pos: usize) -> usize {
+        for block in self.blocks.iter() {
+            if pos < block.len() {
+                return block[pos];
+            }
+            pos -= block.len();
+        }
+        panic!("Position out of range");
+    }
+
+    /// Removes the id and returns the position it was at.
+    fn remove(&mut self, id: usize) -> usize {
+        let pos = self.position(id);
+        let b = self.block_of[id];
+        let idx = self.blocks[b].iter().position(|x| *x == id).unwrap();
+        self.blocks[b].remove(idx);
+        pos
+    }
+
+    fn insert(&mut self, mut pos: usize, id: usize) {
+        let last = self.blocks.len() - 1;
+        let mut b = 0;
+        while b < last && pos > self.blocks[b].len() {
+            pos -= self.blocks[b].len();
+            b += 1;
+        }
+        self.blocks[b].insert(pos, id);
+        self.block_of[id] = b;
+        if self.blocks[b].len() > 2 * self.size {
+            let ids = self.ids().collect();
+            self.split(ids);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_duplicates() {
+        let mut ring = Ring::new(vec![1, 1, 0, 1, -1]);
+        let handles = ring.handles().collect::<Vec<_>>();
+        assert_eq!(ring.find(|x| *x == 1), Some(handles[0]));
+
+        ring.shift(handles[0], 1);
+        assert_eq!(
+            ring.iter().copied().collect::<Vec<_>>(),
+            vec![1, 1, 0, 1, -1]
+        );
+        assert_eq!(ring.position(handles[0]), 1);
+        assert_eq!(ring.position(handles[1]), 0);
+
+        ring.shift(handles[3], 2);
+        assert_eq!(
+            ring.iter().copied().collect::<Vec<_>>(),
+            vec![1, 1, 1, 0, -1]
+        );
+        assert_eq!(ring.position(handles[3]), 1);
+
+        ring.shift(handles[4], -1);
+        assert_eq!(
+            ring.iter().copied().collect::<Vec<_>>(),
+            vec![1, 1, 1, -1, 0]
+        );
+
+        let zero = ring.find(|x| *x == 0).unwrap();
+        assert_eq!(
+            ring.iter_from(zero).copied().collect::<Vec<_>>(),
+            vec![0, 1, 1, 1, -1]
+        );
+        assert_eq!(*ring.at(zero, 4), -1);
+        assert_eq!(*ring.at(zero, -1), -1);
+        assert_eq!(*ring.at(zero, 5), 0);
+        assert_eq!(*ring.get(handles[3]), 1);
+    }
+
+    #[test]
+    fn test_ring_wraps() {
+        let mut ring = Ring::new((0..10).collect::<Vec<i64>>());
+        let h = ring.handles().nth(3).unwrap();
+        ring.shift(h, 9);
+        assert_eq!(ring.position(h), 3);
+        ring.shift(h, -3);
+        assert_eq!(ring.position(h), 9);
+        ring.shift(h, 1000);
+        assert_eq!(ring.iter_from(h).count(), 10);
+        assert_eq!(ring.iter().filter(|x| **x == 3).count(), 1);
+    }
+}